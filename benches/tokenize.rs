@@ -147,7 +147,7 @@ fn tokenize_with_trie<T: TransitionTable<KeyType = char>>(
         .collect()
 }
 
-fn build_trie<T: TransitionTable<KeyType = char>>(vocab: &Vocab) -> (Trie<T>, Vec<u32>) {
+fn build_trie(vocab: &Vocab) -> (Trie<BTreeTransTable<char>>, Vec<u32>) {
     let mut trie = Trie::<BTreeTransTable<_>>::default();
     let mut trie_id_and_token_id = Vec::new();
     for (k, v) in vocab.iter() {
@@ -158,10 +158,18 @@ fn build_trie<T: TransitionTable<KeyType = char>>(vocab: &Vocab) -> (Trie<T>, Ve
     for (u, v) in trie_id_and_token_id.iter() {
         trie_to_token[*u] = *v;
     }
-    (trie.alter_trans_table(), trie_to_token)
+    (trie, trie_to_token)
 }
 
-fn criterion_benchmark<TransTable: TransitionTable<KeyType = char>>(c: &mut Criterion) {
+fn benchmark_with<
+    TransTable: TransitionTable<KeyType = char>,
+    F: FnOnce(GeneralSam<BTreeTransTable<char>>) -> GeneralSam<TransTable>,
+    G: FnOnce(Trie<BTreeTransTable<char>>) -> Trie<TransTable>,
+>(
+    c: &mut Criterion,
+    build_sam: F,
+    convert_trie: G,
+) {
     println!("{}", std::any::type_name::<TransTable>());
 
     println!("building hf_tokenizer...");
@@ -174,10 +182,10 @@ fn criterion_benchmark<TransTable: TransitionTable<KeyType = char>>(c: &mut Crit
     let seq = gen_seq(&vocab);
 
     println!("building trie...");
-    let (trie, trie_to_token) = build_trie::<TransTable>(&vocab);
+    let (trie, trie_to_token) = build_trie(&vocab);
     println!("building sam...");
-    let sam = GeneralSam::<BTreeTransTable<_>>::from_trie(trie.get_root_state())
-        .alter_trans_table_into::<TransTable>();
+    let sam = build_sam(GeneralSam::from_trie(trie.get_root_state()));
+    let trie = convert_trie(trie);
     println!("building greedy tokenizer...");
     let tokenizer =
         GreedyTokenizer::build(&sam, trie.get_root_state(), |tn| trie_to_token[tn.node_id]);
@@ -204,11 +212,33 @@ fn criterion_benchmark<TransTable: TransitionTable<KeyType = char>>(c: &mut Crit
     });
 }
 
+fn criterion_benchmark<TransTable: TransitionTable<KeyType = char>>(c: &mut Criterion) {
+    benchmark_with(
+        c,
+        GeneralSam::alter_trans_table_into::<TransTable>,
+        |trie| trie.alter_trans_table(),
+    );
+}
+
+fn criterion_benchmark_frozen(c: &mut Criterion) {
+    benchmark_with(c, |sam| sam.freeze(), |trie| trie.freeze());
+}
+
+fn criterion_benchmark_double_array(c: &mut Criterion) {
+    benchmark_with(
+        c,
+        |sam| sam.to_double_array(),
+        |trie| trie.to_double_array(),
+    );
+}
+
 criterion_group!(
     benches,
     criterion_benchmark<BTreeTransTable<_>>,
     criterion_benchmark<HashTransTable<_>>,
     criterion_benchmark<VecBisectTable<_>>,
     criterion_benchmark<BoxBisectTable<_>>,
+    criterion_benchmark_frozen,
+    criterion_benchmark_double_array,
 );
 criterion_main!(benches);
@@ -98,12 +98,16 @@ pub mod table;
 pub mod trie_alike;
 
 pub use sam::{
-    GeneralSam, GeneralSamNode, GeneralSamNodeID, GeneralSamState, SAM_NIL_NODE_ID,
-    SAM_ROOT_NODE_ID,
+    CommonSubstring, DocumentIndex, FrozenGeneralSam, FrozenGeneralSamState, FrozenKey,
+    FrozenSamError, GeneralSam, GeneralSamNode, GeneralSamNodeID, GeneralSamState, IndexedSam,
+    Lz77Phrase, MatchingStatistics, NodeData, OccurrenceIter, RepeatClass, RepeatedSubstring,
+    SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID, SlidingWindowSam, SlidingWindowState, SubstringEntry,
+    SubstringIter, SubstringRank, SuffixLinkTree, UniqueSubstring,
 };
 pub use table::{
-    BTreeTransTable, BoxBisectTable, ConstructiveTransitionTable, HashTransTable, SmallAlphabet,
-    TransitionTable, VecBisectTable, WholeAlphabetTable,
+    BTreeTransTable, BoxBisectTable, ConstructiveTransitionTable, DoubleArrayKey, DoubleArrayTable,
+    FlatTransTable, HashTransTable, NodeIdOverflow, NodeIndex, SmallAlphabet, TransitionTable,
+    VecBisectTable, WholeAlphabetTable,
 };
 pub use trie_alike::{IterAsChain, TravelEvent, TrieNodeAlike};
 
@@ -0,0 +1,226 @@
+//! Unique and absent strings of a general suffix automaton.
+
+use std::collections::VecDeque;
+
+use super::distinct::sorted_transitions;
+use super::{GeneralSam, GeneralSamNodeID, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID, TransitionTable};
+
+/// A substring occurring exactly once.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UniqueSubstring<KeyType> {
+    pub keys: Vec<KeyType>,
+    pub node_id: GeneralSamNodeID,
+    /// The sequence id and the start offset of its occurrence.
+    pub position: (usize, usize),
+}
+
+impl<TransTable: TransitionTable> GeneralSam<TransTable>
+where
+    TransTable::KeyType: Ord,
+{
+    /// Returns the parent and the key on the breadth-first search tree
+    /// along the transitions with keys passing `filter` in key order,
+    /// which spells the shortest string of every node reached.
+    ///
+    /// A node is not reached if its shortest string has a key failing `filter`,
+    /// since so do all of its strings.
+    fn bfs_parents<F: FnMut(&TransTable::KeyType) -> bool>(
+        &self,
+        mut filter: F,
+    ) -> Vec<Option<(GeneralSamNodeID, TransTable::KeyType)>> {
+        let mut parents = vec![None; self.num_of_nodes()];
+        let mut queue = VecDeque::from([SAM_ROOT_NODE_ID]);
+        while let Some(node_id) = queue.pop_front() {
+            for (key, next_node_id) in sorted_transitions(self, node_id) {
+                if !filter(&key) {
+                    continue;
+                }
+                if next_node_id != SAM_ROOT_NODE_ID && parents[next_node_id].is_none() {
+                    parents[next_node_id] = Some((node_id, key));
+                    queue.push_back(next_node_id);
+                }
+            }
+        }
+        parents
+    }
+
+    fn spell(
+        parents: &[Option<(GeneralSamNodeID, TransTable::KeyType)>],
+        mut node_id: GeneralSamNodeID,
+    ) -> Vec<TransTable::KeyType> {
+        let mut res = Vec::new();
+        while let Some((parent_id, key)) = &parents[node_id] {
+            res.push(key.clone());
+            node_id = *parent_id;
+        }
+        res.reverse();
+        res
+    }
+
+    /// Returns the minimal unique substrings, which occur exactly once
+    /// while all of their proper substrings occur more than once,
+    /// sorted by length and then lexicographically.
+    ///
+    /// It returns nothing before [`GeneralSam::compute_occurrence_counts`].
+    pub fn minimal_unique_substrings(&self) -> Vec<UniqueSubstring<TransTable::KeyType>> {
+        if self.occurrence_counts.is_empty() {
+            return Vec::new();
+        }
+        let repeated = |node_id: GeneralSamNodeID| {
+            node_id == SAM_ROOT_NODE_ID
+                || self.get_occurrence_count(node_id).is_some_and(|x| x >= 2)
+        };
+
+        let parents = self.bfs_parents(|_| true);
+        let first_occurrences = self.first_occurrence_table();
+        let mut res = Vec::new();
+        for (node_id, node) in self.node_pool.iter().enumerate() {
+            if node_id == SAM_NIL_NODE_ID
+                || node_id == SAM_ROOT_NODE_ID
+                || self.get_occurrence_count(node_id) != Some(1)
+                || !repeated(node.link())
+            {
+                continue;
+            }
+            // the shortest string of the node, whose longest proper prefix
+            // is in the node of its breadth-first search parent
+            let len = self.node_pool[node.link()].len + 1;
+            let Some((parent_id, _)) = &parents[node_id] else {
+                continue;
+            };
+            if !repeated(*parent_id) {
+                continue;
+            }
+            let Some((seq_id, end_pos)) = first_occurrences[node_id] else {
+                continue;
+            };
+            res.push(UniqueSubstring {
+                keys: Self::spell(&parents, node_id),
+                node_id,
+                position: (seq_id, end_pos - len),
+            });
+        }
+        res.sort_by(|a, b| (a.keys.len(), &a.keys).cmp(&(b.keys.len(), &b.keys)));
+        res
+    }
+
+    /// Returns the shortest unique substrings, which are the minimal ones
+    /// of the minimum length, sorted lexicographically.
+    ///
+    /// It returns nothing before [`GeneralSam::compute_occurrence_counts`].
+    pub fn shortest_unique_substrings(&self) -> Vec<UniqueSubstring<TransTable::KeyType>> {
+        let mut res = self.minimal_unique_substrings();
+        if let Some(min_len) = res.first().map(|x| x.keys.len()) {
+            res.retain(|x| x.keys.len() == min_len);
+        }
+        res
+    }
+
+    /// Returns the minimal absent words over `alphabet`, which are not
+    /// substrings while all of their proper substrings are,
+    /// sorted by length and then lexicographically.
+    pub fn minimal_absent_words<Iter: IntoIterator<Item = TransTable::KeyType>>(
+        &self,
+        alphabet: Iter,
+    ) -> Vec<Vec<TransTable::KeyType>> {
+        let mut alphabet: Vec<_> = alphabet.into_iter().collect();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+
+        // nodes whose strings have keys outside the alphabet are not reached
+        let parents = self.bfs_parents(|key| alphabet.binary_search(key).is_ok());
+        let mut res = Vec::new();
+        for (node_id, node) in self.node_pool.iter().enumerate() {
+            if node_id == SAM_NIL_NODE_ID {
+                continue;
+            }
+            if node_id != SAM_ROOT_NODE_ID && parents[node_id].is_none() {
+                continue;
+            }
+            for key in alphabet.iter() {
+                if node.trans.contains_key(key) {
+                    continue;
+                }
+                // the shortest string `s` of the node extended by `key`,
+                // where `s[1..]` is the longest string of the suffix parent
+                if node_id == SAM_ROOT_NODE_ID
+                    || self.node_pool[node.link()].trans.contains_key(key)
+                {
+                    let mut word = Self::spell(&parents, node_id);
+                    word.push(key.clone());
+                    res.push(word);
+                }
+            }
+        }
+        res.sort_by(|a, b| (a.len(), a).cmp(&(b.len(), b)));
+        res
+    }
+
+    /// Returns a lexicographically smallest string over `alphabet`
+    /// among the shortest ones which are not substrings,
+    /// or `None` if `alphabet` is empty.
+    pub fn shortest_absent_string<Iter: IntoIterator<Item = TransTable::KeyType>>(
+        &self,
+        alphabet: Iter,
+    ) -> Option<Vec<TransTable::KeyType>> {
+        let mut alphabet: Vec<_> = alphabet.into_iter().collect();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+
+        // the breadth-first search along the transitions in key order visits
+        // the shortest strings of the nodes by length and then lexicographically
+        let mut parents = vec![None; self.num_of_nodes()];
+        let mut queue = VecDeque::from([SAM_ROOT_NODE_ID]);
+        while let Some(node_id) = queue.pop_front() {
+            let trans = &self.node_pool[node_id].trans;
+            if let Some(key) = alphabet.iter().find(|k| !trans.contains_key(k)) {
+                let mut res = Self::spell(&parents, node_id);
+                res.push(key.clone());
+                return Some(res);
+            }
+            for key in alphabet.iter() {
+                let next_node_id = trans.get_node_id(key)?;
+                if next_node_id != SAM_ROOT_NODE_ID && parents[next_node_id].is_none() {
+                    parents[next_node_id] = Some((node_id, key.clone()));
+                    queue.push_back(next_node_id);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the number of strings of length `len` over `alphabet`
+    /// which are not substrings, or `None` on overflow.
+    pub fn count_absent_strings<Iter: IntoIterator<Item = TransTable::KeyType>>(
+        &self,
+        alphabet: Iter,
+        len: usize,
+    ) -> Option<usize> {
+        let mut alphabet: Vec<_> = alphabet.into_iter().collect();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+
+        let total = u32::try_from(len)
+            .ok()
+            .and_then(|len| alphabet.len().checked_pow(len))?;
+
+        // the number of paths of the current length ending at each node
+        let mut paths = vec![0usize; self.num_of_nodes()];
+        paths[SAM_ROOT_NODE_ID] = 1;
+        for _ in 0..len {
+            let mut next_paths = vec![0usize; self.num_of_nodes()];
+            for (node_id, node) in self.node_pool.iter().enumerate() {
+                if paths[node_id] == 0 {
+                    continue;
+                }
+                for key in alphabet.iter() {
+                    if let Some(next_node_id) = node.trans.get_node_id(key) {
+                        next_paths[next_node_id] += paths[node_id];
+                    }
+                }
+            }
+            paths = next_paths;
+        }
+        Some(total - paths.iter().sum::<usize>())
+    }
+}
@@ -0,0 +1,177 @@
+//! Statistics of the distinct substrings in a general suffix automaton.
+
+use super::{
+    GeneralSam, GeneralSamNodeID, NodeIndex, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID, TransitionTable,
+};
+
+impl<TransTable: TransitionTable> GeneralSam<TransTable> {
+    /// Returns the number of distinct non-empty substrings.
+    pub fn count_distinct_substrings(&self) -> usize {
+        self.node_pool
+            .iter()
+            .enumerate()
+            .filter(|(node_id, _)| *node_id != SAM_NIL_NODE_ID && *node_id != SAM_ROOT_NODE_ID)
+            .map(|(_, node)| node.len - self.node_pool[node.link()].len)
+            .sum()
+    }
+
+    /// Returns the number of distinct substrings of every length,
+    /// where the `l`-th element counts the ones of length `l`,
+    /// and the empty string is not counted.
+    pub fn distinct_substrings_by_length(&self) -> Vec<usize> {
+        let max_len = self.node_pool.iter().map(|x| x.len).max().unwrap_or(0);
+        let mut diff = vec![0isize; max_len + 2];
+        self.node_pool
+            .iter()
+            .enumerate()
+            .filter(|(node_id, _)| *node_id != SAM_NIL_NODE_ID && *node_id != SAM_ROOT_NODE_ID)
+            .for_each(|(_, node)| {
+                diff[self.node_pool[node.link()].len + 1] += 1;
+                diff[node.len + 1] -= 1;
+            });
+
+        let mut res = Vec::with_capacity(max_len + 1);
+        let mut acc = 0;
+        diff[..=max_len].iter().for_each(|d| {
+            acc += d;
+            res.push(acc as usize);
+        });
+        res
+    }
+}
+
+/// Numbers of the substrings starting from every node,
+/// used to answer order statistics of the substrings
+/// in lexicographic order.
+#[derive(Clone, Debug)]
+pub struct SubstringRank {
+    weights: Vec<usize>,
+    path_counts: Vec<usize>,
+}
+
+impl SubstringRank {
+    /// Counts every distinct substring once.
+    pub fn build<TransTable: TransitionTable>(sam: &GeneralSam<TransTable>) -> Self {
+        let weights = (0..sam.num_of_nodes())
+            .map(|node_id| (node_id != SAM_NIL_NODE_ID && node_id != SAM_ROOT_NODE_ID) as usize)
+            .collect();
+        Self::build_with_weights(sam, weights)
+    }
+
+    /// Counts every substring as many times as it occurs.
+    ///
+    /// Returns `None` if [`GeneralSam::compute_occurrence_counts`]
+    /// has not been called.
+    pub fn build_with_multiplicity<TransTable: TransitionTable>(
+        sam: &GeneralSam<TransTable>,
+    ) -> Option<Self> {
+        let weights = (0..sam.num_of_nodes())
+            .map(|node_id| match node_id {
+                SAM_NIL_NODE_ID | SAM_ROOT_NODE_ID => Some(0),
+                _ => sam.get_occurrence_count(node_id),
+            })
+            .collect::<Option<_>>()?;
+        Some(Self::build_with_weights(sam, weights))
+    }
+
+    fn build_with_weights<TransTable: TransitionTable>(
+        sam: &GeneralSam<TransTable>,
+        weights: Vec<usize>,
+    ) -> Self {
+        let mut path_counts = weights.clone();
+        sam.sorted_node_ids().iter().rev().for_each(|node_id| {
+            let node = &sam.node_pool[*node_id];
+            path_counts[*node_id] += node
+                .trans
+                .transitions()
+                .map(|next_node_id| path_counts[next_node_id.index()])
+                .sum::<usize>();
+        });
+        Self {
+            weights,
+            path_counts,
+        }
+    }
+
+    /// Returns the number of non-empty substrings being counted.
+    pub fn num_of_substrings(&self) -> usize {
+        self.path_counts.get(SAM_ROOT_NODE_ID).copied().unwrap_or(0)
+    }
+
+    /// Returns the `k`-th (0-indexed) smallest substring
+    /// in lexicographic order, or `None` if `k` is out of range.
+    pub fn kth_substring<TransTable: TransitionTable>(
+        &self,
+        sam: &GeneralSam<TransTable>,
+        mut k: usize,
+    ) -> Option<Vec<TransTable::KeyType>>
+    where
+        TransTable::KeyType: Ord,
+    {
+        let mut res = Vec::new();
+        let mut node_id = SAM_ROOT_NODE_ID;
+        loop {
+            let (key, next_node_id) =
+                sorted_transitions(sam, node_id)
+                    .into_iter()
+                    .find(|(_, next_node_id)| {
+                        if k < self.path_counts[*next_node_id] {
+                            return true;
+                        }
+                        k -= self.path_counts[*next_node_id];
+                        false
+                    })?;
+            res.push(key);
+            node_id = next_node_id;
+            if k < self.weights[node_id] {
+                return Some(res);
+            }
+            k -= self.weights[node_id];
+        }
+    }
+
+    /// Returns the 0-indexed rank of `pattern` in lexicographic order,
+    /// i.e. the number of counted substrings smaller than it,
+    /// or `None` if it is not a non-empty substring.
+    ///
+    /// It is the inverse of [`SubstringRank::kth_substring`].
+    pub fn rank<TransTable: TransitionTable, Iter: IntoIterator<Item = TransTable::KeyType>>(
+        &self,
+        sam: &GeneralSam<TransTable>,
+        pattern: Iter,
+    ) -> Option<usize>
+    where
+        TransTable::KeyType: Ord,
+    {
+        let mut res = 0;
+        let mut node_id = SAM_ROOT_NODE_ID;
+        for key in pattern {
+            res += self.weights[node_id];
+            let node = sam.get_node(node_id)?;
+            res += node
+                .trans
+                .iter()
+                .filter(|(k, _)| *k < key)
+                .map(|(_, next_node_id)| self.path_counts[next_node_id.index()])
+                .sum::<usize>();
+            node_id = node.trans.get_node_id(&key)?;
+        }
+        (node_id != SAM_ROOT_NODE_ID).then_some(res)
+    }
+}
+
+pub(super) fn sorted_transitions<TransTable: TransitionTable>(
+    sam: &GeneralSam<TransTable>,
+    node_id: GeneralSamNodeID,
+) -> Vec<(TransTable::KeyType, GeneralSamNodeID)>
+where
+    TransTable::KeyType: Ord,
+{
+    let mut res: Vec<_> = sam.node_pool[node_id]
+        .trans
+        .iter()
+        .map(|(k, v)| (k, v.index()))
+        .collect();
+    res.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    res
+}
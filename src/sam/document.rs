@@ -0,0 +1,89 @@
+//! Document membership of the nodes in a general suffix automaton.
+
+use std::convert::Infallible;
+
+use super::{
+    GeneralSam, GeneralSamNodeID, GeneralSamState, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID,
+    TransitionTable,
+};
+use crate::{TravelEvent, TrieNodeAlike};
+
+/// Documents containing the strings of each node.
+///
+/// Every accepting trie node is colored as a document,
+/// whose content is the path from the trie root to it.
+#[derive(Clone, Debug)]
+pub struct DocumentIndex {
+    documents: Vec<Vec<usize>>,
+}
+
+impl DocumentIndex {
+    /// Colors the nodes of `sam`, which is built by [`GeneralSam::from_trie`]
+    /// with `trie_node`, and `f` tags each accepting trie node with its
+    /// document id.
+    pub fn build<
+        TransTable: TransitionTable,
+        TN: TrieNodeAlike<InnerType = TransTable::KeyType> + Clone,
+        F: FnMut(&TN) -> usize,
+    >(
+        sam: &GeneralSam<TransTable>,
+        trie_node: TN,
+        mut f: F,
+    ) -> Self {
+        let mut documents = vec![Vec::new(); sam.num_of_nodes()];
+        let mut last_doc_ids = vec![None; sam.num_of_nodes()];
+        let mut path = Vec::new();
+
+        let mut color = |path: &[GeneralSamNodeID], doc_id: usize| {
+            for &prefix_id in path.iter().chain(Some(&SAM_ROOT_NODE_ID)) {
+                let mut node_id = prefix_id;
+                while node_id != SAM_NIL_NODE_ID && last_doc_ids[node_id] != Some(doc_id) {
+                    last_doc_ids[node_id] = Some(doc_id);
+                    documents[node_id].push(doc_id);
+                    node_id = sam.get_node(node_id).unwrap().get_suffix_parent_id();
+                }
+            }
+        };
+
+        let callback = |event: TravelEvent<(&GeneralSamState<_, &GeneralSam<_>>, &TN), _, _>| -> Result<_, Infallible> {
+            match event {
+                TravelEvent::PushRoot((_, tn)) => {
+                    if tn.is_accepting() {
+                        color(&path, f(tn));
+                    }
+                }
+                TravelEvent::Push((state, tn), _, _) => {
+                    path.push(state.node_id);
+                    if tn.is_accepting() {
+                        color(&path, f(tn));
+                    }
+                }
+                TravelEvent::Pop(..) => {
+                    path.pop();
+                }
+            }
+            Ok(())
+        };
+        sam.get_root_state().dfs_along(trie_node, callback).unwrap();
+
+        documents.iter_mut().for_each(|x| {
+            x.sort_unstable();
+            x.dedup();
+        });
+
+        Self { documents }
+    }
+
+    /// Returns the sorted ids of the documents containing the strings of the node.
+    pub fn get_documents(&self, node_id: GeneralSamNodeID) -> &[usize] {
+        self.documents
+            .get(node_id)
+            .map(|x| x.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns the number of distinct documents containing the strings of the node.
+    pub fn get_document_frequency(&self, node_id: GeneralSamNodeID) -> usize {
+        self.get_documents(node_id).len()
+    }
+}
@@ -0,0 +1,403 @@
+//! A frozen general suffix automaton queried in place over a byte buffer.
+//!
+//! The layout is, with integers in little endian:
+//!
+//! - the header: the magic `b"GSAMFRZN"`, the format version and the key
+//!   size as `u32`, the numbers of nodes and edges as `u64`, and the FNV-1a
+//!   checksum of the rest of the buffer as `u64`;
+//! - the node records: the length, the suffix link, the index of the first
+//!   edge and the flags as `u64`, where the lowest bit of the flags tells
+//!   whether the node is accepting;
+//! - the keys of the edges in [`FrozenKey`] encoding, sorted in every node;
+//! - the target node ids of the edges as `u64`.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use super::{
+    GeneralSam, GeneralSamNodeID, NodeIndex, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID, TransitionTable,
+};
+
+const MAGIC: &[u8; 8] = b"GSAMFRZN";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 40;
+const NODE_SIZE: usize = 32;
+const ID_SIZE: usize = 8;
+const ACCEPT_FLAG: u64 = 1;
+
+/// A key type with a fixed-width encoding of at most 8 bytes,
+/// whose encoded bytes compare in the same order as the keys.
+pub trait FrozenKey: Copy + Ord {
+    const SIZE: usize;
+
+    /// Writes the first [`FrozenKey::SIZE`] bytes of `buf`.
+    fn encode(&self, buf: &mut [u8]);
+
+    /// Reads the first [`FrozenKey::SIZE`] bytes of `buf`.
+    fn decode(buf: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_frozen_key_for_uint {
+    ($($t:ty),*) => {
+        $(
+            impl FrozenKey for $t {
+                const SIZE: usize = size_of::<$t>();
+
+                fn encode(&self, buf: &mut [u8]) {
+                    buf[..Self::SIZE].copy_from_slice(&self.to_be_bytes());
+                }
+
+                fn decode(buf: &[u8]) -> Option<Self> {
+                    buf.get(..Self::SIZE)?.try_into().ok().map(Self::from_be_bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_frozen_key_for_uint!(u8, u16, u32, u64);
+
+impl FrozenKey for char {
+    const SIZE: usize = 4;
+
+    fn encode(&self, buf: &mut [u8]) {
+        (*self as u32).encode(buf)
+    }
+
+    fn decode(buf: &[u8]) -> Option<Self> {
+        u32::decode(buf).and_then(char::from_u32)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FrozenSamError {
+    Truncated,
+    BadMagic,
+    UnsupportedVersion(u32),
+    KeySizeMismatch { expected: usize, found: usize },
+    ChecksumMismatch,
+    InvalidNode(GeneralSamNodeID),
+}
+
+impl fmt::Display for FrozenSamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "the buffer is truncated"),
+            Self::BadMagic => write!(f, "the buffer is not a frozen suffix automaton"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported version {version}"),
+            Self::KeySizeMismatch { expected, found } => {
+                write!(f, "expected keys of {expected} bytes, found {found}")
+            }
+            Self::ChecksumMismatch => write!(f, "checksum mismatch"),
+            Self::InvalidNode(node_id) => write!(f, "invalid node {node_id}"),
+        }
+    }
+}
+
+impl std::error::Error for FrozenSamError {}
+
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+impl<TransTable: TransitionTable> GeneralSam<TransTable>
+where
+    TransTable::KeyType: FrozenKey,
+{
+    /// Encodes the automaton into the layout read by [`FrozenGeneralSam`].
+    ///
+    /// The accepting flags are taken as is, see [`GeneralSam::refresh`].
+    pub fn to_frozen_bytes(&self) -> Vec<u8> {
+        let key_size = TransTable::KeyType::SIZE;
+        let mut nodes = Vec::with_capacity(self.num_of_nodes() * NODE_SIZE);
+        let mut keys = Vec::new();
+        let mut targets = Vec::new();
+        let mut num_of_edges = 0u64;
+        for node in self.node_pool.iter() {
+            let mut edges: Vec<_> = node.trans.iter().collect();
+            edges.sort_unstable_by_key(|x| x.0);
+
+            let flags = if node.accept { ACCEPT_FLAG } else { 0 };
+            for x in [node.len as u64, node.link() as u64, num_of_edges, flags] {
+                nodes.extend_from_slice(&x.to_le_bytes());
+            }
+            for (key, target) in edges {
+                let offset = keys.len();
+                keys.resize(offset + key_size, 0);
+                key.encode(&mut keys[offset..]);
+                targets.extend_from_slice(&(target.index() as u64).to_le_bytes());
+                num_of_edges += 1;
+            }
+        }
+
+        let mut res = Vec::with_capacity(HEADER_SIZE + nodes.len() + keys.len() + targets.len());
+        res.extend_from_slice(MAGIC);
+        res.extend_from_slice(&VERSION.to_le_bytes());
+        res.extend_from_slice(&(key_size as u32).to_le_bytes());
+        res.extend_from_slice(&(self.num_of_nodes() as u64).to_le_bytes());
+        res.extend_from_slice(&num_of_edges.to_le_bytes());
+        res.extend_from_slice(&[0; 8]);
+        res.extend_from_slice(&nodes);
+        res.extend_from_slice(&keys);
+        res.extend_from_slice(&targets);
+        let checksum = fnv1a(&res[HEADER_SIZE..]);
+        res[HEADER_SIZE - 8..HEADER_SIZE].copy_from_slice(&checksum.to_le_bytes());
+        res
+    }
+}
+
+/// A general suffix automaton borrowing a buffer
+/// from [`GeneralSam::to_frozen_bytes`], e.g. a memory-mapped file,
+/// and queried without deserialization.
+#[derive(Clone, Copy, Debug)]
+pub struct FrozenGeneralSam<'a, KeyType: FrozenKey> {
+    nodes: &'a [u8],
+    keys: &'a [u8],
+    targets: &'a [u8],
+    num_of_nodes: usize,
+    num_of_edges: usize,
+    phantom: PhantomData<KeyType>,
+}
+
+impl<'a, KeyType: FrozenKey> FrozenGeneralSam<'a, KeyType> {
+    /// Checks the header, the checksum and every node of the buffer,
+    /// so the queries never go out of bounds.
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, FrozenSamError> {
+        if data.len() < HEADER_SIZE {
+            return Err(FrozenSamError::Truncated);
+        }
+        if &data[..8] != MAGIC {
+            return Err(FrozenSamError::BadMagic);
+        }
+        let version = u32::from_le_bytes(data[8..12].try_into().unwrap());
+        if version != VERSION {
+            return Err(FrozenSamError::UnsupportedVersion(version));
+        }
+        let key_size = u32::from_le_bytes(data[12..16].try_into().unwrap()) as usize;
+        if key_size != KeyType::SIZE {
+            return Err(FrozenSamError::KeySizeMismatch {
+                expected: KeyType::SIZE,
+                found: key_size,
+            });
+        }
+
+        let to_usize = |x: u64| usize::try_from(x).map_err(|_| FrozenSamError::Truncated);
+        let num_of_nodes = to_usize(read_u64(data, 16))?;
+        let num_of_edges = to_usize(read_u64(data, 24))?;
+        let body_len = num_of_nodes
+            .checked_mul(NODE_SIZE)
+            .zip(num_of_edges.checked_mul(key_size + ID_SIZE))
+            .and_then(|(x, y)| x.checked_add(y))
+            .ok_or(FrozenSamError::Truncated)?;
+        if data.len() - HEADER_SIZE != body_len {
+            return Err(FrozenSamError::Truncated);
+        }
+        if read_u64(data, 32) != fnv1a(&data[HEADER_SIZE..]) {
+            return Err(FrozenSamError::ChecksumMismatch);
+        }
+
+        let (nodes, edges) = data[HEADER_SIZE..].split_at(num_of_nodes * NODE_SIZE);
+        let (keys, targets) = edges.split_at(num_of_edges * key_size);
+        let res = Self {
+            nodes,
+            keys,
+            targets,
+            num_of_nodes,
+            num_of_edges,
+            phantom: PhantomData,
+        };
+        res.validate()?;
+        Ok(res)
+    }
+
+    fn validate(&self) -> Result<(), FrozenSamError> {
+        if self.num_of_nodes <= SAM_ROOT_NODE_ID {
+            return Err(FrozenSamError::InvalidNode(SAM_ROOT_NODE_ID));
+        }
+        let mut edge_end = 0;
+        for node_id in 0..self.num_of_nodes {
+            let invalid = FrozenSamError::InvalidNode(node_id);
+            let record = &self.nodes[node_id * NODE_SIZE..];
+            if read_u64(record, 8) >= self.num_of_nodes as u64
+                || read_u64(record, 16) != edge_end as u64
+            {
+                return Err(invalid);
+            }
+            let edges = self.edge_range(node_id);
+            if edges.start > edges.end
+                || edges.end > self.num_of_edges
+                || (node_id == SAM_NIL_NODE_ID && !edges.is_empty())
+            {
+                return Err(invalid);
+            }
+            for i in edges.clone() {
+                if KeyType::decode(self.key_bytes(i)).is_none()
+                    || (i > edges.start && self.key_bytes(i - 1) >= self.key_bytes(i))
+                    || read_u64(self.targets, i * ID_SIZE) >= self.num_of_nodes as u64
+                {
+                    return Err(invalid);
+                }
+            }
+            edge_end = edges.end;
+        }
+        Ok(())
+    }
+
+    pub fn num_of_nodes(&self) -> usize {
+        self.num_of_nodes
+    }
+
+    pub fn num_of_edges(&self) -> usize {
+        self.num_of_edges
+    }
+
+    pub fn get_root_state(&self) -> FrozenGeneralSamState<'a, KeyType> {
+        self.get_state(SAM_ROOT_NODE_ID)
+    }
+
+    /// Returns the state of the node, or the nil state if it does not exist.
+    pub fn get_state(&self, node_id: GeneralSamNodeID) -> FrozenGeneralSamState<'a, KeyType> {
+        FrozenGeneralSamState {
+            sam: *self,
+            node_id: if node_id < self.num_of_nodes {
+                node_id
+            } else {
+                SAM_NIL_NODE_ID
+            },
+        }
+    }
+
+    /// Returns the node id, or the nil node id if it is out of range,
+    /// since a state may hold any id in its public field.
+    fn checked_node_id(&self, node_id: GeneralSamNodeID) -> GeneralSamNodeID {
+        if node_id < self.num_of_nodes {
+            node_id
+        } else {
+            SAM_NIL_NODE_ID
+        }
+    }
+
+    fn node_field(&self, node_id: GeneralSamNodeID, field: usize) -> u64 {
+        let node_id = self.checked_node_id(node_id);
+        read_u64(self.nodes, node_id * NODE_SIZE + field * 8)
+    }
+
+    fn edge_range(&self, node_id: GeneralSamNodeID) -> std::ops::Range<usize> {
+        let node_id = self.checked_node_id(node_id);
+        let start = self.node_field(node_id, 2) as usize;
+        let end = if node_id + 1 < self.num_of_nodes {
+            self.node_field(node_id + 1, 2) as usize
+        } else {
+            self.num_of_edges
+        };
+        start..end
+    }
+
+    fn key_bytes(&self, edge: usize) -> &'a [u8] {
+        &self.keys[edge * KeyType::SIZE..(edge + 1) * KeyType::SIZE]
+    }
+
+    fn target(&self, edge: usize) -> GeneralSamNodeID {
+        read_u64(self.targets, edge * ID_SIZE) as GeneralSamNodeID
+    }
+
+    fn find_edge(&self, node_id: GeneralSamNodeID, key: &KeyType) -> Option<usize> {
+        let mut buf = [0; 8];
+        key.encode(&mut buf);
+        let key = &buf[..KeyType::SIZE];
+        let edges = self.edge_range(node_id);
+        let (mut lo, mut hi) = (edges.start, edges.end);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            match self.key_bytes(mid).cmp(key) {
+                std::cmp::Ordering::Equal => return Some(mid),
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+            }
+        }
+        None
+    }
+}
+
+/// A state of a [`FrozenGeneralSam`], mirroring
+/// the feeding API of [`super::GeneralSamState`].
+#[derive(Clone, Copy, Debug)]
+pub struct FrozenGeneralSamState<'a, KeyType: FrozenKey> {
+    pub sam: FrozenGeneralSam<'a, KeyType>,
+    pub node_id: GeneralSamNodeID,
+}
+
+impl<KeyType: FrozenKey> FrozenGeneralSamState<'_, KeyType> {
+    pub fn is_nil(&self) -> bool {
+        self.node_id == SAM_NIL_NODE_ID
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.node_id == SAM_ROOT_NODE_ID
+    }
+
+    pub fn is_accepting(&self) -> bool {
+        self.sam.node_field(self.node_id, 3) & ACCEPT_FLAG != 0
+    }
+
+    pub fn max_suffix_len(&self) -> usize {
+        self.sam.node_field(self.node_id, 0) as usize
+    }
+
+    /// Returns an iterator over the keys and the target node ids
+    /// of the transitions in key order.
+    pub fn transitions(&self) -> impl Iterator<Item = (KeyType, GeneralSamNodeID)> + '_ {
+        self.sam.edge_range(self.node_id).map(|i| {
+            let key = KeyType::decode(self.sam.key_bytes(i)).expect("validated key");
+            (key, self.sam.target(i))
+        })
+    }
+
+    pub fn goto_suffix_parent(&mut self) -> &mut Self {
+        self.node_id = self.sam.node_field(self.node_id, 1) as GeneralSamNodeID;
+        self
+    }
+
+    pub fn goto(&mut self, key: &KeyType) -> &mut Self {
+        self.node_id = match self.sam.find_edge(self.node_id, key) {
+            Some(edge) => self.sam.target(edge),
+            None => SAM_NIL_NODE_ID,
+        };
+        self
+    }
+
+    pub fn feed<Seq: IntoIterator<Item = KeyType>>(&mut self, seq: Seq) -> &mut Self {
+        for key in seq {
+            if self.is_nil() {
+                break;
+            }
+            self.goto(&key);
+        }
+        self
+    }
+
+    pub fn feed_ref<'s, Seq: IntoIterator<Item = &'s KeyType>>(&mut self, seq: Seq) -> &mut Self
+    where
+        KeyType: 's,
+    {
+        self.feed(seq.into_iter().copied())
+    }
+}
+
+impl FrozenGeneralSamState<'_, u8> {
+    pub fn feed_bytes<S: AsRef<[u8]>>(&mut self, seq: S) -> &mut Self {
+        self.feed_ref(seq.as_ref())
+    }
+}
+
+impl FrozenGeneralSamState<'_, char> {
+    pub fn feed_chars<S: AsRef<str>>(&mut self, seq: S) -> &mut Self {
+        self.feed(seq.as_ref().chars())
+    }
+}
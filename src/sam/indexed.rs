@@ -0,0 +1,114 @@
+//! A general suffix automaton indexed by positions of its sequences.
+
+use super::{
+    GeneralSam, GeneralSamNodeID, GeneralSamState, OccurrenceIter, SAM_NIL_NODE_ID,
+    SAM_ROOT_NODE_ID, SuffixLinkTree,
+};
+use crate::{ConstructiveTransitionTable, TransitionTable};
+
+/// A general suffix automaton over a list of sequences,
+/// which records the prefix node of every end position,
+/// so the node of any substring `seq[l..r]` is located
+/// in `O(log n)` without feeding it.
+#[derive(Clone, Debug)]
+pub struct IndexedSam<TransTable: TransitionTable> {
+    sam: GeneralSam<TransTable>,
+    tree: SuffixLinkTree,
+    prefix_node_ids: Vec<Vec<GeneralSamNodeID>>,
+}
+
+impl<TransTable: ConstructiveTransitionTable> IndexedSam<TransTable> {
+    /// Builds the automaton by inserting `seqs` in order,
+    /// the sequence ids are their indices.
+    pub fn from_seqs<
+        Seq: IntoIterator<Item = TransTable::KeyType>,
+        Iter: IntoIterator<Item = Seq>,
+    >(
+        seqs: Iter,
+    ) -> Self {
+        let mut sam = GeneralSam::default();
+        let prefix_node_ids = seqs
+            .into_iter()
+            .map(|seq| {
+                let mut node_ids = Vec::new();
+                sam.insert_with_prefixes(seq, |node_id| node_ids.push(node_id));
+                node_ids
+            })
+            .collect();
+        sam.refresh();
+        let tree = SuffixLinkTree::build(&sam);
+        Self {
+            sam,
+            tree,
+            prefix_node_ids,
+        }
+    }
+}
+
+impl<TransTable: TransitionTable> IndexedSam<TransTable> {
+    pub fn get_sam(&self) -> &GeneralSam<TransTable> {
+        &self.sam
+    }
+
+    pub fn get_suffix_link_tree(&self) -> &SuffixLinkTree {
+        &self.tree
+    }
+
+    pub fn num_of_seqs(&self) -> usize {
+        self.prefix_node_ids.len()
+    }
+
+    /// Returns the length of the sequence, or `None` if it does not exist.
+    pub fn get_seq_len(&self, seq_id: usize) -> Option<usize> {
+        self.prefix_node_ids.get(seq_id).map(|x| x.len())
+    }
+
+    /// Returns the node id of `seq[l..r]`.
+    ///
+    /// Returns `None` if the sequence or the range does not exist.
+    pub fn node_id_of_range(&self, seq_id: usize, l: usize, r: usize) -> Option<GeneralSamNodeID> {
+        let prefix_node_ids = self.prefix_node_ids.get(seq_id)?;
+        if l > r || r > prefix_node_ids.len() {
+            return None;
+        }
+        if l == r {
+            return Some(SAM_ROOT_NODE_ID);
+        }
+        self.tree.climb_while(prefix_node_ids[r - 1], |node_id| {
+            self.sam
+                .get_node(node_id)
+                .is_some_and(|node| node.max_suffix_len() >= r - l)
+        })
+    }
+
+    /// Returns the state of `seq[l..r]`, or the nil state
+    /// if the sequence or the range does not exist.
+    pub fn state_of_range(
+        &self,
+        seq_id: usize,
+        l: usize,
+        r: usize,
+    ) -> GeneralSamState<TransTable, &GeneralSam<TransTable>> {
+        let node_id = self
+            .node_id_of_range(seq_id, l, r)
+            .unwrap_or(SAM_NIL_NODE_ID);
+        self.sam.get_state(node_id)
+    }
+
+    /// Returns an iterator over the sequence ids and the start offsets
+    /// of all occurrences of `seq[l..r]`.
+    ///
+    /// See [`SuffixLinkTree::occurrences`].
+    pub fn occurrences_of_range(
+        &self,
+        seq_id: usize,
+        l: usize,
+        r: usize,
+    ) -> OccurrenceIter<'_, TransTable> {
+        let node_id = self
+            .node_id_of_range(seq_id, l, r)
+            .unwrap_or(SAM_NIL_NODE_ID);
+        self.tree
+            .occurrences(&self.sam, node_id, r.saturating_sub(l))
+    }
+}
@@ -0,0 +1,217 @@
+//! The suffix link tree of a general suffix automaton.
+
+use std::ops::Range;
+
+use super::{GeneralSam, GeneralSamNodeID, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID, TransitionTable};
+
+/// The tree formed by suffix links, rooted at the root node.
+///
+/// The nil node is not part of the tree.
+#[derive(Clone, Debug)]
+pub struct SuffixLinkTree {
+    offsets: Vec<usize>,
+    children: Vec<GeneralSamNodeID>,
+    depth: Vec<usize>,
+    entry_time: Vec<usize>,
+    exit_time: Vec<usize>,
+    dfs_order: Vec<GeneralSamNodeID>,
+    ancestors: Vec<Vec<GeneralSamNodeID>>,
+}
+
+impl SuffixLinkTree {
+    pub fn build<TransTable: TransitionTable>(sam: &GeneralSam<TransTable>) -> Self {
+        let num_of_nodes = sam.num_of_nodes();
+        let parents: Vec<GeneralSamNodeID> = (0..num_of_nodes)
+            .map(|node_id| match node_id {
+                SAM_NIL_NODE_ID | SAM_ROOT_NODE_ID => SAM_NIL_NODE_ID,
+                _ => sam.get_node(node_id).unwrap().get_suffix_parent_id(),
+            })
+            .collect();
+
+        let mut offsets = vec![0; num_of_nodes + 1];
+        (SAM_ROOT_NODE_ID + 1..num_of_nodes).for_each(|node_id| {
+            offsets[parents[node_id] + 1] += 1;
+        });
+        (0..num_of_nodes).for_each(|i| offsets[i + 1] += offsets[i]);
+
+        let mut children = vec![SAM_NIL_NODE_ID; offsets[num_of_nodes]];
+        let mut heads = offsets.clone();
+        (SAM_ROOT_NODE_ID + 1..num_of_nodes).for_each(|node_id| {
+            let parent_id = parents[node_id];
+            children[heads[parent_id]] = node_id;
+            heads[parent_id] += 1;
+        });
+
+        let mut depth = vec![0; num_of_nodes];
+        let mut entry_time = vec![0; num_of_nodes];
+        let mut exit_time = vec![0; num_of_nodes];
+        let mut dfs_order = Vec::with_capacity(num_of_nodes);
+        let mut max_depth = 0;
+
+        let mut stack = vec![(SAM_ROOT_NODE_ID, false)];
+        while let Some((node_id, exiting)) = stack.pop() {
+            if exiting {
+                exit_time[node_id] = dfs_order.len();
+                continue;
+            }
+            entry_time[node_id] = dfs_order.len();
+            dfs_order.push(node_id);
+            stack.push((node_id, true));
+            children[offsets[node_id]..offsets[node_id + 1]]
+                .iter()
+                .rev()
+                .for_each(|&child_id| {
+                    depth[child_id] = depth[node_id] + 1;
+                    max_depth = max_depth.max(depth[child_id]);
+                    stack.push((child_id, false));
+                });
+        }
+
+        let mut ancestors = vec![parents];
+        while (1 << ancestors.len()) <= max_depth {
+            let last = ancestors.last().unwrap();
+            let next = last.iter().map(|&x| last[x]).collect();
+            ancestors.push(next);
+        }
+
+        Self {
+            offsets,
+            children,
+            depth,
+            entry_time,
+            exit_time,
+            dfs_order,
+            ancestors,
+        }
+    }
+
+    pub fn num_of_nodes(&self) -> usize {
+        self.depth.len()
+    }
+
+    fn contains(&self, node_id: GeneralSamNodeID) -> bool {
+        node_id != SAM_NIL_NODE_ID && node_id < self.num_of_nodes()
+    }
+
+    pub fn get_parent(&self, node_id: GeneralSamNodeID) -> Option<GeneralSamNodeID> {
+        self.ancestors[0]
+            .get(node_id)
+            .copied()
+            .filter(|&x| x != SAM_NIL_NODE_ID)
+    }
+
+    pub fn get_children(&self, node_id: GeneralSamNodeID) -> &[GeneralSamNodeID] {
+        match (self.offsets.get(node_id), self.offsets.get(node_id + 1)) {
+            (Some(&lo), Some(&hi)) if self.contains(node_id) => &self.children[lo..hi],
+            _ => &[],
+        }
+    }
+
+    /// Returns the number of suffix links from the node to the root.
+    pub fn get_depth(&self, node_id: GeneralSamNodeID) -> Option<usize> {
+        self.depth
+            .get(node_id)
+            .copied()
+            .filter(|_| self.contains(node_id))
+    }
+
+    /// Returns the nodes in the order they are entered by a depth-first search.
+    pub fn get_dfs_order(&self) -> &[GeneralSamNodeID] {
+        &self.dfs_order
+    }
+
+    /// Returns the index of the node in the depth-first search order.
+    pub fn get_entry_time(&self, node_id: GeneralSamNodeID) -> Option<usize> {
+        self.entry_time
+            .get(node_id)
+            .copied()
+            .filter(|_| self.contains(node_id))
+    }
+
+    /// Returns the index right after the last descendant of the node
+    /// in the depth-first search order.
+    pub fn get_exit_time(&self, node_id: GeneralSamNodeID) -> Option<usize> {
+        self.exit_time
+            .get(node_id)
+            .copied()
+            .filter(|_| self.contains(node_id))
+    }
+
+    /// Returns the range of the subtree of the node in the depth-first search
+    /// order, i.e. `get_dfs_order()[range]` are the node and its descendants.
+    pub fn get_subtree_range(&self, node_id: GeneralSamNodeID) -> Option<Range<usize>> {
+        Some(self.get_entry_time(node_id)?..self.get_exit_time(node_id)?)
+    }
+
+    /// Returns whether `ancestor_id` is `node_id` or one of its ancestors.
+    pub fn is_ancestor(&self, ancestor_id: GeneralSamNodeID, node_id: GeneralSamNodeID) -> bool {
+        match (
+            self.get_subtree_range(ancestor_id),
+            self.get_entry_time(node_id),
+        ) {
+            (Some(range), Some(time)) => range.contains(&time),
+            _ => false,
+        }
+    }
+
+    /// Returns the `k`-th ancestor of the node with binary lifting.
+    pub fn get_kth_ancestor(
+        &self,
+        node_id: GeneralSamNodeID,
+        k: usize,
+    ) -> Option<GeneralSamNodeID> {
+        if k > self.get_depth(node_id)? {
+            return None;
+        }
+        let mut res = node_id;
+        self.ancestors.iter().enumerate().for_each(|(i, table)| {
+            if (k >> i) & 1 == 1 {
+                res = table[res];
+            }
+        });
+        Some(res)
+    }
+
+    /// Returns the highest ancestor (or the node itself) such that
+    /// `predicate` holds for all nodes on the path to it,
+    /// where `predicate` must be monotone along the path.
+    ///
+    /// Returns `None` if `predicate` does not hold for the node.
+    pub fn climb_while<F: FnMut(GeneralSamNodeID) -> bool>(
+        &self,
+        node_id: GeneralSamNodeID,
+        mut predicate: F,
+    ) -> Option<GeneralSamNodeID> {
+        if !self.contains(node_id) || !predicate(node_id) {
+            return None;
+        }
+        let mut res = node_id;
+        for table in self.ancestors.iter().rev() {
+            let next = table[res];
+            if next != SAM_NIL_NODE_ID && predicate(next) {
+                res = next;
+            }
+        }
+        Some(res)
+    }
+
+    /// Returns the lowest common ancestor of two nodes.
+    pub fn lca(&self, u: GeneralSamNodeID, v: GeneralSamNodeID) -> Option<GeneralSamNodeID> {
+        let (du, dv) = (self.get_depth(u)?, self.get_depth(v)?);
+        let (mut u, mut v) = if du >= dv {
+            (self.get_kth_ancestor(u, du - dv)?, v)
+        } else {
+            (u, self.get_kth_ancestor(v, dv - du)?)
+        };
+        if u == v {
+            return Some(u);
+        }
+        for table in self.ancestors.iter().rev() {
+            if table[u] != table[v] {
+                u = table[u];
+                v = table[v];
+            }
+        }
+        Some(self.ancestors[0][u])
+    }
+}
@@ -0,0 +1,147 @@
+//! Lempel-Ziv factorization with a general suffix automaton.
+
+use super::{GeneralSam, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID, TransitionTable};
+
+/// A phrase of a Lempel-Ziv factorization.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Lz77Phrase<KeyType> {
+    /// A key never seen before.
+    Literal(KeyType),
+    /// A copy of `len` keys starting at `start` of the sequence `seq_id`.
+    Copy {
+        seq_id: usize,
+        start: usize,
+        len: usize,
+    },
+}
+
+impl<KeyType> Lz77Phrase<KeyType> {
+    /// Returns the number of keys covered by the phrase.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Literal(_) => 1,
+            Self::Copy { len, .. } => *len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<KeyType> Lz77Phrase<KeyType> {
+    /// Returns the copy of `len` keys ending at `first_occurrence`.
+    fn copy(first_occurrence: Option<(usize, usize)>, len: usize) -> Self {
+        let (seq_id, end_pos) = first_occurrence.expect("a node with a prefix in its subtree");
+        Self::Copy {
+            seq_id,
+            start: end_pos - len,
+            len,
+        }
+    }
+}
+
+impl<TransTable: TransitionTable> GeneralSam<TransTable> {
+    /// Returns the greedy LZ77 factorization of `seq`,
+    /// which is the sequence `seq_id` in the automaton,
+    /// where every copy is the longest previous factor at its start,
+    /// i.e. its source is in an earlier sequence, or starts before the phrase
+    /// in the same sequence but may overlap with it.
+    ///
+    /// A copy is extended while the first occurrence of it is such a source,
+    /// so the sources are the first occurrences.
+    /// For an automaton built from `seq` alone, e.g. by [`GeneralSam::from_bytes`],
+    /// `seq_id` is `0` and so is the sequence id of every copy.
+    ///
+    /// Returns `None` if `seq_id` is not less than [`GeneralSam::num_of_seqs`].
+    /// The phrases are meaningless if `seq` is not the sequence `seq_id`.
+    ///
+    /// The first occurrences are folded for the call
+    /// unless they are computed by [`GeneralSam::compute_first_occurrences`].
+    pub fn lz77_factorize<Iter: IntoIterator<Item = TransTable::KeyType>>(
+        &self,
+        seq_id: usize,
+        seq: Iter,
+    ) -> Option<Vec<Lz77Phrase<TransTable::KeyType>>> {
+        if seq_id >= self.num_of_seqs() {
+            return None;
+        }
+        let first_occurrences = self.first_occurrence_table();
+        let mut node_id = SAM_ROOT_NODE_ID;
+        let mut len = 0;
+        let mut pos = 0;
+        let mut res = Vec::new();
+
+        let mut iter = seq.into_iter().peekable();
+        while let Some(key) = iter.peek() {
+            let next_node_id =
+                self.node_pool[node_id]
+                    .trans
+                    .get_node_id(key)
+                    .filter(|next_node_id| {
+                        first_occurrences[*next_node_id].is_some_and(|(src_seq_id, end_pos)| {
+                            src_seq_id < seq_id
+                                || (src_seq_id == seq_id && end_pos - (len + 1) < pos)
+                        })
+                    });
+            if let Some(next_node_id) = next_node_id {
+                iter.next();
+                node_id = next_node_id;
+                len += 1;
+            } else if len == 0 {
+                res.push(Lz77Phrase::Literal(iter.next().unwrap()));
+                pos += 1;
+            } else {
+                res.push(Lz77Phrase::copy(first_occurrences[node_id], len));
+                node_id = SAM_ROOT_NODE_ID;
+                pos += len;
+                len = 0;
+            }
+        }
+        if len > 0 {
+            res.push(Lz77Phrase::copy(first_occurrences[node_id], len));
+        }
+        Some(res)
+    }
+
+    /// Returns the greedy factorization of `query` relative to the sequences
+    /// in the automaton, where every copy is the longest substring
+    /// of the automaton at its start, pointing to its first occurrence.
+    ///
+    /// The keys absent from the automaton become literals.
+    ///
+    /// The first occurrences are folded for the call
+    /// unless they are computed by [`GeneralSam::compute_first_occurrences`].
+    pub fn relative_factorize<Iter: IntoIterator<Item = TransTable::KeyType>>(
+        &self,
+        query: Iter,
+    ) -> Vec<Lz77Phrase<TransTable::KeyType>> {
+        let first_occurrences = self.first_occurrence_table();
+        let mut node_id = SAM_ROOT_NODE_ID;
+        let mut len = 0;
+        let mut res = Vec::new();
+
+        let mut iter = query.into_iter().peekable();
+        while let Some(key) = iter.peek() {
+            let next_node_id = self.node_pool[node_id]
+                .trans
+                .get_node_id(key)
+                .unwrap_or(SAM_NIL_NODE_ID);
+            if next_node_id != SAM_NIL_NODE_ID {
+                iter.next();
+                node_id = next_node_id;
+                len += 1;
+            } else if len == 0 {
+                res.push(Lz77Phrase::Literal(iter.next().unwrap()));
+            } else {
+                res.push(Lz77Phrase::copy(first_occurrences[node_id], len));
+                node_id = SAM_ROOT_NODE_ID;
+                len = 0;
+            }
+        }
+        if len > 0 {
+            res.push(Lz77Phrase::copy(first_occurrences[node_id], len));
+        }
+        res
+    }
+}
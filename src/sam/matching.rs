@@ -0,0 +1,157 @@
+//! Matching statistics of a text against a general suffix automaton.
+
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+
+use super::{GeneralSam, GeneralSamNodeID, GeneralSamState, SAM_ROOT_NODE_ID, TransitionTable};
+
+/// An iterator yielding, for every position of a text,
+/// the longest suffix of the scanned text which is a substring
+/// in the automaton, as its length and its state.
+///
+/// On a mismatch, it falls back along the suffix links
+/// instead of stopping at the nil state.
+#[derive(Clone, Debug)]
+pub struct MatchingStatistics<
+    TransTable: TransitionTable,
+    SamRef: Borrow<GeneralSam<TransTable>>,
+    Iter: Iterator<Item = TransTable::KeyType>,
+> {
+    state: GeneralSamState<TransTable, SamRef>,
+    len: usize,
+    iter: Iter,
+}
+
+impl<
+    TransTable: TransitionTable,
+    SamRef: Borrow<GeneralSam<TransTable>> + Clone,
+    Iter: Iterator<Item = TransTable::KeyType>,
+> MatchingStatistics<TransTable, SamRef, Iter>
+{
+    /// Starts scanning from `state`, which is taken as matching
+    /// the longest string in it.
+    /// A nil state, or one with an out-of-range node id,
+    /// is reset to the root state.
+    pub fn new(mut state: GeneralSamState<TransTable, SamRef>, iter: Iter) -> Self {
+        if state.is_nil() || state.get_node().is_none() {
+            state.node_id = SAM_ROOT_NODE_ID;
+        }
+        let len = state.get_node().map(|x| x.max_suffix_len()).unwrap_or(0);
+        Self { state, len, iter }
+    }
+
+    /// Returns the length of the current match and its state.
+    pub fn current(&self) -> (usize, &GeneralSamState<TransTable, SamRef>) {
+        (self.len, &self.state)
+    }
+}
+
+impl<
+    TransTable: TransitionTable,
+    SamRef: Borrow<GeneralSam<TransTable>> + Clone,
+    Iter: Iterator<Item = TransTable::KeyType>,
+> Iterator for MatchingStatistics<TransTable, SamRef, Iter>
+{
+    type Item = (usize, GeneralSamState<TransTable, SamRef>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.iter.next()?;
+        loop {
+            if let Some(next_state) = self.state.get_non_nil_trans(&key) {
+                self.state = next_state;
+                self.len += 1;
+                break;
+            }
+            if self.state.is_root() {
+                self.len = 0;
+                break;
+            }
+            if self.state.is_nil() {
+                // the nil node has no suffix parent to fall back to
+                self.state.node_id = SAM_ROOT_NODE_ID;
+                self.len = 0;
+                break;
+            }
+            self.state.goto_suffix_parent();
+            self.len = self
+                .state
+                .get_node()
+                .map(|x| x.max_suffix_len())
+                .unwrap_or(0);
+        }
+        Some((self.len, self.state.clone()))
+    }
+}
+
+/// A common substring of the automaton and a query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CommonSubstring {
+    /// The length of the substring.
+    pub len: usize,
+    /// The start offset of the substring in the query.
+    pub query_pos: usize,
+    /// The node of the substring in the automaton.
+    pub node_id: GeneralSamNodeID,
+}
+
+impl<TransTable: TransitionTable> GeneralSam<TransTable> {
+    /// Returns the longest common substring of the automaton and `query`,
+    /// taking the leftmost one in the query among the equally long ones.
+    ///
+    /// Returns `None` if they have no non-empty common substring.
+    pub fn longest_common_substring<Iter: IntoIterator<Item = TransTable::KeyType>>(
+        &self,
+        query: Iter,
+    ) -> Option<CommonSubstring> {
+        let mut res: Option<CommonSubstring> = None;
+        for (i, (len, state)) in self.get_root_state().matching_statistics(query).enumerate() {
+            if len > 0 && res.is_none_or(|x| len > x.len) {
+                res = Some(CommonSubstring {
+                    len,
+                    query_pos: i + 1 - len,
+                    node_id: state.node_id,
+                });
+            }
+        }
+        res
+    }
+
+    /// Returns at most `k` longest distinct common substrings
+    /// of the automaton and `query`, from the longest to the shortest.
+    ///
+    /// Only maximal matches are considered, i.e. the ones that cannot be
+    /// extended to the right in the query, so a reported substring is
+    /// never merely a suffix of a longer match at the same position.
+    /// A substring matched at several positions is reported once,
+    /// at its leftmost position in the query.
+    pub fn top_k_common_substrings<Iter: IntoIterator<Item = TransTable::KeyType>>(
+        &self,
+        query: Iter,
+        k: usize,
+    ) -> Vec<CommonSubstring> {
+        let mut candidates = BTreeMap::new();
+        let mut add_candidate = |x: Option<CommonSubstring>| {
+            if let Some(x) = x.filter(|x| x.len > 0) {
+                candidates.entry((x.node_id, x.len)).or_insert(x);
+            }
+        };
+
+        let mut last: Option<CommonSubstring> = None;
+        for (i, (len, state)) in self.get_root_state().matching_statistics(query).enumerate() {
+            if last.is_some_and(|x| len != x.len + 1) {
+                add_candidate(last);
+            }
+            last = Some(CommonSubstring {
+                len,
+                query_pos: i + 1 - len,
+                node_id: state.node_id,
+            });
+        }
+        add_candidate(last);
+
+        let mut res: Vec<CommonSubstring> = candidates.into_values().collect();
+        res.sort_by(|a, b| b.len.cmp(&a.len).then(a.query_pos.cmp(&b.query_pos)));
+        res.truncate(k);
+        res
+    }
+}
@@ -1,12 +1,44 @@
 //! A general suffix automaton implementation.
 
+mod absent;
+mod distinct;
+mod document;
+mod frozen;
+mod indexed;
+mod link_tree;
+mod lz77;
+mod matching;
+mod node_data;
+mod occurrence;
+mod repeats;
+#[cfg(feature = "serde")]
+mod serialize;
 mod state;
+mod substrings;
+mod window;
+pub use absent::UniqueSubstring;
+pub use distinct::SubstringRank;
+pub use document::DocumentIndex;
+pub use frozen::{FrozenGeneralSam, FrozenGeneralSamState, FrozenKey, FrozenSamError};
+pub use indexed::IndexedSam;
+pub use link_tree::SuffixLinkTree;
+pub use lz77::Lz77Phrase;
+pub use matching::{CommonSubstring, MatchingStatistics};
+pub use node_data::NodeData;
+pub use occurrence::OccurrenceIter;
+pub use repeats::{RepeatClass, RepeatedSubstring};
 pub use state::GeneralSamState;
+pub use substrings::{SubstringEntry, SubstringIter};
+pub use window::{SlidingWindowSam, SlidingWindowState};
 
-use std::convert::Infallible;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
 
+use crate::table::convert_trans_table;
 use crate::{
-    ConstructiveTransitionTable, IterAsChain, TransitionTable, TravelEvent, TrieNodeAlike,
+    ConstructiveTransitionTable, DoubleArrayKey, DoubleArrayTable, FlatTransTable, IterAsChain,
+    NodeIdOverflow, NodeIndex, TransitionTable, TravelEvent, TrieNodeAlike,
 };
 
 pub type GeneralSamNodeID = usize;
@@ -14,18 +46,55 @@ pub const SAM_NIL_NODE_ID: GeneralSamNodeID = 0;
 pub const SAM_ROOT_NODE_ID: GeneralSamNodeID = 1;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "TransTable: serde::Serialize, TransTable::NodeID: serde::Serialize",
+        deserialize = "TransTable: serde::Deserialize<'de>, \
+                       TransTable::NodeID: serde::Deserialize<'de>"
+    ))
+)]
 pub struct GeneralSamNode<TransTable: TransitionTable> {
     trans: TransTable,
     len: usize,
-    link: GeneralSamNodeID,
+    link: TransTable::NodeID,
     accept: bool,
+    prefix: bool,
 }
 
 /// A general suffix automaton.
+///
+/// Node ids are stored as [`TransitionTable::NodeID`],
+/// e.g. `BTreeTransTable<u8, u32>` halves the size of ids on 64-bit targets,
+/// and the construction panics once the ids overflow it.
+/// Build with [`GeneralSam::try_from_trie`], [`GeneralSam::try_insert`]
+/// and [`GeneralSam::try_append`], or with a wide table narrowed by
+/// [`GeneralSam::try_alter_trans_table`], to fail without panicking.
+///
+/// The sequence ends and the sequence ids of the prefixes are kept
+/// in side tables, which are sparse for the usual constructions,
+/// and the occurrence counts and the first occurrences are opt-in,
+/// so the nodes stay as small as the transitions and the links.
+///
+/// With the `serde` feature, it can be serialized,
+/// and the node ids are validated on deserialization.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(bound = "TransTable: serde::Serialize, TransTable::NodeID: serde::Serialize")
+)]
 pub struct GeneralSam<TransTable: TransitionTable> {
     node_pool: Vec<GeneralSamNode<TransTable>>,
     topo_and_suf_len_sorted_order: Vec<GeneralSamNodeID>,
+    occurrence_counts: Vec<usize>,
+    first_occurrences: Vec<Option<(usize, usize)>>,
+    seq_node_starts: Vec<GeneralSamNodeID>,
+    /// The nil node if the latest insertion failed, which cannot be extended.
+    last_seq_end: GeneralSamNodeID,
+    end_counts: BTreeMap<GeneralSamNodeID, usize>,
+    prefix_seq_ids: BTreeMap<GeneralSamNodeID, Vec<usize>>,
 }
 
 impl<TransTable: ConstructiveTransitionTable> GeneralSamNode<TransTable> {
@@ -34,7 +103,8 @@ impl<TransTable: ConstructiveTransitionTable> GeneralSamNode<TransTable> {
             trans: Default::default(),
             accept,
             len,
-            link,
+            link: TransTable::NodeID::from_index(link),
+            prefix: false,
         }
     }
 }
@@ -49,21 +119,43 @@ impl<TransTable: TransitionTable> GeneralSamNode<TransTable> {
     }
 
     pub fn get_suffix_parent_id(&self) -> GeneralSamNodeID {
-        self.link
+        self.link()
+    }
+
+    fn link(&self) -> GeneralSamNodeID {
+        self.link.index()
     }
 
     pub fn get_trans(&self) -> &TransTable {
         &self.trans
     }
 
+    /// Returns whether the node is the state of a whole prefix
+    /// of the inserted sequences, i.e. it is not a clone.
+    /// See [`GeneralSam::get_prefix_seq_ids`] for the sequences having it.
+    pub fn is_prefix(&self) -> bool {
+        self.prefix
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the node ids overflow the new node id type.
     fn alter_trans_table<NewTableType: TransitionTable<KeyType = TransTable::KeyType>>(
         &self,
+    ) -> GeneralSamNode<NewTableType> {
+        self.with_trans(convert_trans_table(&self.trans))
+    }
+
+    fn with_trans<NewTableType: TransitionTable>(
+        &self,
+        trans: NewTableType,
     ) -> GeneralSamNode<NewTableType> {
         GeneralSamNode {
-            trans: NewTableType::from_kv_iter(self.trans.iter()),
+            trans,
             accept: self.accept,
             len: self.len,
-            link: self.link,
+            link: NewTableType::NodeID::from_index(self.link()),
+            prefix: self.prefix,
         }
     }
 }
@@ -73,6 +165,10 @@ impl<TransTable: ConstructiveTransitionTable<KeyType = u8>> GeneralSam<TransTabl
         let iter = IterAsChain::from(s.as_ref().iter().copied());
         Self::from_trie(iter)
     }
+
+    pub fn insert_bytes<S: AsRef<[u8]>>(&mut self, bytes: S) -> GeneralSamNodeID {
+        self.insert(bytes.as_ref().iter().copied())
+    }
 }
 
 impl<TransTable: ConstructiveTransitionTable<KeyType = u32>> GeneralSam<TransTable> {
@@ -80,6 +176,10 @@ impl<TransTable: ConstructiveTransitionTable<KeyType = u32>> GeneralSam<TransTab
         let iter = IterAsChain::from(s.as_ref().iter().copied());
         Self::from_trie(iter)
     }
+
+    pub fn insert_utf32<S: AsRef<[u32]>>(&mut self, s: S) -> GeneralSamNodeID {
+        self.insert(s.as_ref().iter().copied())
+    }
 }
 
 impl<TransTable: ConstructiveTransitionTable<KeyType = char>> GeneralSam<TransTable> {
@@ -87,9 +187,15 @@ impl<TransTable: ConstructiveTransitionTable<KeyType = char>> GeneralSam<TransTa
         let iter = IterAsChain::from(s.as_ref().chars());
         Self::from_trie(iter)
     }
+
+    pub fn insert_chars<S: AsRef<str>>(&mut self, s: S) -> GeneralSamNodeID {
+        self.insert(s.as_ref().chars())
+    }
 }
 
 impl<TransTable: ConstructiveTransitionTable> Default for GeneralSam<TransTable> {
+    /// The root of an empty automaton accepts the empty string,
+    /// until the accepting flags are rebuilt by [`GeneralSam::refresh`].
     fn default() -> Self {
         Self {
             node_pool: vec![
@@ -97,6 +203,12 @@ impl<TransTable: ConstructiveTransitionTable> Default for GeneralSam<TransTable>
                 GeneralSamNode::new(true, 0, SAM_NIL_NODE_ID),
             ],
             topo_and_suf_len_sorted_order: Default::default(),
+            occurrence_counts: Default::default(),
+            first_occurrences: Default::default(),
+            seq_node_starts: Default::default(),
+            last_seq_end: SAM_ROOT_NODE_ID,
+            end_counts: Default::default(),
+            prefix_seq_ids: Default::default(),
         }
     }
 }
@@ -106,6 +218,14 @@ impl<TransTable: TransitionTable> GeneralSam<TransTable> {
         self.node_pool.len()
     }
 
+    /// Returns the number of sequences in the automaton.
+    ///
+    /// An automaton built by [`GeneralSam::from_trie`] counts as one sequence,
+    /// whose end positions are depths in the trie.
+    pub fn num_of_seqs(&self) -> usize {
+        self.seq_node_starts.len()
+    }
+
     pub fn get_root_node(&self) -> &GeneralSamNode<TransTable> {
         self.get_node(SAM_ROOT_NODE_ID).unwrap()
     }
@@ -136,19 +256,188 @@ impl<TransTable: TransitionTable> GeneralSam<TransTable> {
         &self.topo_and_suf_len_sorted_order
     }
 
+    /// Returns the topological order, which is sorted by the maximum suffix
+    /// lengths for the caller if it is outdated by [`GeneralSam::append`]
+    /// or [`GeneralSam::insert`] before [`GeneralSam::refresh`].
+    fn sorted_node_ids(&self) -> Cow<'_, [GeneralSamNodeID]> {
+        if self.topo_and_suf_len_sorted_order.len() + 1 == self.num_of_nodes() {
+            Cow::Borrowed(&self.topo_and_suf_len_sorted_order)
+        } else {
+            // both the transitions and the suffix links go between
+            // nodes of different lengths, so the lengths keep both orders
+            let mut res: Vec<_> = (SAM_ROOT_NODE_ID..self.num_of_nodes()).collect();
+            res.sort_by_key(|node_id| self.node_pool[*node_id].len);
+            Cow::Owned(res)
+        }
+    }
+
+    /// Returns the ids of all sequences having the whole prefix of the node
+    /// in increasing order, whose end position is the maximum suffix length.
+    ///
+    /// Sequences sharing a prefix share its node,
+    /// and each of them is an end position of the node.
+    pub fn get_prefix_seq_ids(
+        &self,
+        node_id: GeneralSamNodeID,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let (seq_id, seq_ids) = self.prefix_seq_id_parts(node_id);
+        seq_id.into_iter().chain(seq_ids.iter().copied())
+    }
+
+    /// Returns the ids of the sequences having the whole prefix of the node,
+    /// which are either the one allocating the node,
+    /// or all listed in the side table if it is not the only one.
+    fn prefix_seq_id_parts(&self, node_id: GeneralSamNodeID) -> (Option<usize>, &[usize]) {
+        match self.prefix_seq_ids.get(&node_id) {
+            Some(seq_ids) => (None, seq_ids),
+            None => (
+                self.get_node(node_id)
+                    .filter(|node| node.is_prefix())
+                    .map(|_| self.alloc_seq_id(node_id)),
+                &[],
+            ),
+        }
+    }
+
+    /// Returns the id of the sequence being inserted when the node is allocated.
+    fn alloc_seq_id(&self, node_id: GeneralSamNodeID) -> usize {
+        self.seq_node_starts
+            .partition_point(|x| *x <= node_id)
+            .saturating_sub(1)
+    }
+
+    fn num_of_prefix_seqs(&self, node_id: GeneralSamNodeID) -> usize {
+        let (seq_id, seq_ids) = self.prefix_seq_id_parts(node_id);
+        seq_id.is_some() as usize + seq_ids.len()
+    }
+
+    /// Returns the number of occurrences of the strings in the node,
+    /// i.e. the size of their end position set.
+    ///
+    /// It is available after [`GeneralSam::compute_occurrence_counts`],
+    /// until the automaton is extended without [`GeneralSam::refresh`].
+    pub fn get_occurrence_count(&self, node_id: GeneralSamNodeID) -> Option<usize> {
+        self.occurrence_counts
+            .get(node_id)
+            .copied()
+            .filter(|_| self.occurrence_counts.len() == self.num_of_nodes())
+    }
+
+    /// Computes the occurrence counts of all nodes along the suffix links.
+    ///
+    /// For a general suffix automaton built from a trie,
+    /// each trie node is counted as one end position.
+    /// The counts are kept up to date by [`GeneralSam::refresh`] afterwards.
+    pub fn compute_occurrence_counts(&mut self) {
+        self.occurrence_counts = self
+            .fold_suffix_tree(
+                |node_id, _| self.num_of_prefix_seqs(node_id),
+                |p, c| *p += c,
+            )
+            .into_vec();
+    }
+
+    /// Returns the sequence id and the end position
+    /// of the first occurrence of the strings in the node,
+    /// where the end position is the length of the prefix ending there.
+    ///
+    /// An automaton built by [`GeneralSam::from_trie`] is one sequence,
+    /// so the sequence id is always `0` and the end position is a trie depth,
+    /// build it by [`GeneralSam::insert`] or [`IndexedSam`] instead
+    /// to tell the strings apart.
+    ///
+    /// It is available after [`GeneralSam::compute_first_occurrences`],
+    /// until the automaton is extended without [`GeneralSam::refresh`].
+    pub fn get_first_occurrence(&self, node_id: GeneralSamNodeID) -> Option<(usize, usize)> {
+        self.first_occurrences
+            .get(node_id)
+            .copied()
+            .flatten()
+            .filter(|_| self.first_occurrences.len() == self.num_of_nodes())
+    }
+
+    /// Computes the first occurrences of all nodes along the suffix links,
+    /// which are the earliest prefixes in their subtrees.
+    ///
+    /// The first occurrences are kept up to date by [`GeneralSam::refresh`] afterwards.
+    pub fn compute_first_occurrences(&mut self) {
+        self.first_occurrences = self.fold_first_occurrences();
+    }
+
+    fn fold_first_occurrences(&self) -> Vec<Option<(usize, usize)>> {
+        self.fold_suffix_tree(
+            |node_id, node| {
+                let (seq_id, seq_ids) = self.prefix_seq_id_parts(node_id);
+                seq_id
+                    .or(seq_ids.first().copied())
+                    .map(|seq_id| (seq_id, node.len))
+            },
+            |p, c| {
+                *p = match (*p, *c) {
+                    (Some(p), Some(c)) => Some(p.min(c)),
+                    (p, c) => p.or(c),
+                }
+            },
+        )
+        .into_vec()
+    }
+
+    /// Returns the first occurrences of all nodes,
+    /// which are folded for the caller unless they are computed
+    /// by [`GeneralSam::compute_first_occurrences`] and up to date.
+    fn first_occurrence_table(&self) -> Cow<'_, [Option<(usize, usize)>]> {
+        if self.first_occurrences.len() == self.num_of_nodes() {
+            Cow::Borrowed(&self.first_occurrences)
+        } else {
+            Cow::Owned(self.fold_first_occurrences())
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the node ids overflow [`TransitionTable::NodeID`] of the new table,
+    /// see [`GeneralSam::try_alter_trans_table`].
     pub fn alter_trans_table<NewTableType: TransitionTable<KeyType = TransTable::KeyType>>(
         &self,
     ) -> GeneralSam<NewTableType> {
-        GeneralSam {
-            node_pool: self
-                .node_pool
+        self.with_node_pool(
+            self.node_pool
                 .iter()
                 .map(|x| x.alter_trans_table())
                 .collect(),
+        )
+    }
+
+    /// Replaces the nodes by `node_pool`, cloning the other tables.
+    fn with_node_pool<NewTableType: TransitionTable>(
+        &self,
+        node_pool: Vec<GeneralSamNode<NewTableType>>,
+    ) -> GeneralSam<NewTableType> {
+        GeneralSam {
+            node_pool,
             topo_and_suf_len_sorted_order: self.topo_and_suf_len_sorted_order.clone(),
+            occurrence_counts: self.occurrence_counts.clone(),
+            first_occurrences: self.first_occurrences.clone(),
+            seq_node_starts: self.seq_node_starts.clone(),
+            last_seq_end: self.last_seq_end,
+            end_counts: self.end_counts.clone(),
+            prefix_seq_ids: self.prefix_seq_ids.clone(),
         }
     }
 
+    /// Like [`GeneralSam::alter_trans_table`],
+    /// but fails if the node ids overflow the new node id type.
+    pub fn try_alter_trans_table<NewTableType: TransitionTable<KeyType = TransTable::KeyType>>(
+        &self,
+    ) -> Result<GeneralSam<NewTableType>, NodeIdOverflow> {
+        NodeIdOverflow::check::<NewTableType::NodeID>(self.num_of_nodes())?;
+        Ok(self.alter_trans_table())
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the node ids overflow the new node id type,
+    /// see [`GeneralSam::try_alter_trans_table`].
     pub fn alter_trans_table_into<NewTableType: TransitionTable<KeyType = TransTable::KeyType>>(
         self,
     ) -> GeneralSam<NewTableType> {
@@ -159,42 +448,250 @@ impl<TransTable: TransitionTable> GeneralSam<TransTable> {
                 .map(|x| x.alter_trans_table())
                 .collect(),
             topo_and_suf_len_sorted_order: self.topo_and_suf_len_sorted_order,
+            occurrence_counts: self.occurrence_counts,
+            first_occurrences: self.first_occurrences,
+            seq_node_starts: self.seq_node_starts,
+            last_seq_end: self.last_seq_end,
+            end_counts: self.end_counts,
+            prefix_seq_ids: self.prefix_seq_ids,
         }
     }
 }
 
+impl<TransTable: TransitionTable> GeneralSam<TransTable>
+where
+    TransTable::KeyType: Ord,
+{
+    /// Packs the transitions of all nodes into a single edge array,
+    /// sorted by keys within every node, see [`FlatTransTable`].
+    pub fn freeze(&self) -> GeneralSam<FlatTransTable<TransTable::KeyType, TransTable::NodeID>> {
+        let tables = FlatTransTable::build_all(self.node_pool.iter().map(|node| &node.trans));
+        self.with_node_pool(
+            self.node_pool
+                .iter()
+                .zip(tables)
+                .map(|(node, trans)| node.with_trans(trans))
+                .collect(),
+        )
+    }
+}
+
+impl<TransTable: TransitionTable> GeneralSam<TransTable>
+where
+    TransTable::KeyType: DoubleArrayKey,
+{
+    /// Packs the transitions of all nodes into a single double array,
+    /// see [`DoubleArrayTable`].
+    pub fn to_double_array(
+        &self,
+    ) -> GeneralSam<DoubleArrayTable<TransTable::KeyType, TransTable::NodeID>> {
+        let tables = DoubleArrayTable::build_all(self.node_pool.iter().map(|node| &node.trans));
+        self.with_node_pool(
+            self.node_pool
+                .iter()
+                .zip(tables)
+                .map(|(node, trans)| node.with_trans(trans))
+                .collect(),
+        )
+    }
+}
+
 impl<TransTable: ConstructiveTransitionTable> GeneralSam<TransTable> {
+    /// # Panics
+    ///
+    /// Panics if the node ids overflow [`TransitionTable::NodeID`],
+    /// see [`GeneralSam::try_from_trie`].
     pub fn from_trie<TN: TrieNodeAlike>(node: TN) -> Self
+    where
+        TN::InnerType: Into<TransTable::KeyType>,
+    {
+        Self::try_from_trie(node).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Like [`GeneralSam::from_trie`],
+    /// but fails if the node ids overflow [`TransitionTable::NodeID`].
+    pub fn try_from_trie<TN: TrieNodeAlike>(node: TN) -> Result<Self, NodeIdOverflow>
     where
         TN::InnerType: Into<TransTable::KeyType>,
     {
         let mut sam = Self::default();
 
-        let accept_empty_string = node.is_accepting();
+        sam.seq_node_starts.push(sam.num_of_nodes());
+        if node.is_accepting() {
+            sam.add_end(SAM_ROOT_NODE_ID);
+        }
 
-        sam.build_with_trie(node);
+        sam.build_with_trie(node)?;
         sam.topo_sort_with_queue();
         sam.update_accepting();
 
-        sam.node_pool[SAM_ROOT_NODE_ID].accept = accept_empty_string;
+        Ok(sam)
+    }
+
+    /// Appends `key` to the sequence ending at `last_node_id`,
+    /// and returns the node id of the extended sequence.
+    ///
+    /// `last_node_id` is expected to be the end of the latest sequence,
+    /// i.e. the result of the previous appending or insertion,
+    /// or the root state to start a new sequence.
+    /// For an automaton built by [`GeneralSam::from_trie`],
+    /// the latest sequence ends at the last trie node in the breadth-first order,
+    /// e.g. the whole sequence of [`GeneralSam::from_bytes`].
+    /// The sequence may share its prefix with the inserted ones,
+    /// nodes are split or reused when the transition already exists.
+    /// The end mark of the sequence moves to the returned node,
+    /// so only the suffixes of the grown sequence are accepted,
+    /// while other sequences ending at `last_node_id` keep their marks.
+    ///
+    /// The topological order and the accepting flags are not maintained here,
+    /// call [`GeneralSam::refresh`] after a batch of appending.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `last_node_id` is neither the end of the latest sequence
+    /// nor the root, since the appended positions would go to a wrong sequence,
+    /// or if the node ids overflow [`TransitionTable::NodeID`],
+    /// see [`GeneralSam::try_append`].
+    pub fn append<Key: Into<TransTable::KeyType>>(
+        &mut self,
+        last_node_id: GeneralSamNodeID,
+        key: Key,
+    ) -> GeneralSamNodeID {
+        self.try_append(last_node_id, key)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Like [`GeneralSam::append`], but fails without changing the automaton
+    /// if the node ids overflow [`TransitionTable::NodeID`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `last_node_id` is neither the end of the latest sequence
+    /// nor the root.
+    pub fn try_append<Key: Into<TransTable::KeyType>>(
+        &mut self,
+        last_node_id: GeneralSamNodeID,
+        key: Key,
+    ) -> Result<GeneralSamNodeID, NodeIdOverflow> {
+        let key = key.into();
+        let extending = self.num_of_seqs() > 0 && last_node_id == self.last_seq_end;
+        assert!(
+            extending || last_node_id == SAM_ROOT_NODE_ID,
+            "node {last_node_id} is neither the end of the latest sequence nor the root"
+        );
+        self.check_node_trans(last_node_id, &key)?;
+        if !extending {
+            self.seq_node_starts.push(self.num_of_nodes());
+        }
+        let new_node_id = self.insert_node_trans(last_node_id, key, true, self.num_of_seqs() - 1);
+        if extending {
+            self.remove_end(last_node_id);
+        }
+        self.last_seq_end = new_node_id;
+        Ok(new_node_id)
+    }
 
-        sam
+    /// Inserts a new sequence, and returns the node id of the whole sequence.
+    ///
+    /// The sequence id, used by [`GeneralSam::get_first_occurrence`],
+    /// is the number of sequences before the insertion.
+    ///
+    /// The automaton accepts the same language as the one built by
+    /// [`GeneralSam::from_trie`] over all the inserted sequences,
+    /// once [`GeneralSam::refresh`] is called after a batch of insertion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node ids overflow [`TransitionTable::NodeID`],
+    /// see [`GeneralSam::try_insert`].
+    pub fn insert<Iter: IntoIterator<Item = TransTable::KeyType>>(
+        &mut self,
+        iter: Iter,
+    ) -> GeneralSamNodeID {
+        self.insert_with_prefixes(iter, |_| {})
+    }
+
+    /// Like [`GeneralSam::insert`],
+    /// but fails if the node ids overflow [`TransitionTable::NodeID`].
+    ///
+    /// On failure, no node is half split, but the nodes of the keys
+    /// before the overflowing one remain as the prefixes of a sequence
+    /// taking the next sequence id, which is neither accepted
+    /// nor extended by [`GeneralSam::append`] afterwards.
+    pub fn try_insert<Iter: IntoIterator<Item = TransTable::KeyType>>(
+        &mut self,
+        iter: Iter,
+    ) -> Result<GeneralSamNodeID, NodeIdOverflow> {
+        self.try_insert_with_prefixes(iter, |_| {})
+    }
+
+    /// Inserts a new sequence like [`GeneralSam::insert`],
+    /// and calls `f` with the node id of every non-empty prefix.
+    fn insert_with_prefixes<
+        Iter: IntoIterator<Item = TransTable::KeyType>,
+        F: FnMut(GeneralSamNodeID),
+    >(
+        &mut self,
+        iter: Iter,
+        f: F,
+    ) -> GeneralSamNodeID {
+        self.try_insert_with_prefixes(iter, f)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn try_insert_with_prefixes<
+        Iter: IntoIterator<Item = TransTable::KeyType>,
+        F: FnMut(GeneralSamNodeID),
+    >(
+        &mut self,
+        iter: Iter,
+        mut f: F,
+    ) -> Result<GeneralSamNodeID, NodeIdOverflow> {
+        let seq_id = self.num_of_seqs();
+        self.seq_node_starts.push(self.num_of_nodes());
+        let mut current = SAM_ROOT_NODE_ID;
+        for key in iter {
+            if let Err(err) = self.check_node_trans(current, &key) {
+                self.last_seq_end = SAM_NIL_NODE_ID;
+                return Err(err);
+            }
+            current = self.insert_node_trans(current, key, false, seq_id);
+            f(current);
+        }
+        self.add_end(current);
+        self.last_seq_end = current;
+        Ok(current)
+    }
+
+    /// Rebuilds the topological order and the accepting flags,
+    /// which are outdated by [`GeneralSam::append`] and [`GeneralSam::insert`].
+    pub fn refresh(&mut self) {
+        self.topo_and_suf_len_sorted_order.clear();
+        self.topo_sort_with_queue();
+        self.update_accepting();
+        if !self.occurrence_counts.is_empty() {
+            self.compute_occurrence_counts();
+        }
+        if !self.first_occurrences.is_empty() {
+            self.compute_first_occurrences();
+        }
     }
 
-    fn build_with_trie<TN: TrieNodeAlike>(&mut self, node: TN)
+    fn build_with_trie<TN: TrieNodeAlike>(&mut self, node: TN) -> Result<(), NodeIdOverflow>
     where
         TN::InnerType: Into<TransTable::KeyType>,
     {
-        node.bfs_travel(|event| -> Result<GeneralSamNodeID, Infallible> {
-            match event {
-                TravelEvent::PushRoot(_) => Ok(SAM_ROOT_NODE_ID),
-                TravelEvent::Push(cur_tn, cur_node_id, key) => {
-                    Ok(self.insert_node_trans(*cur_node_id, key, cur_tn.is_accepting()))
-                }
-                TravelEvent::Pop(_, cur_node_id) => Ok(cur_node_id),
+        node.bfs_travel(|event| match event {
+            TravelEvent::PushRoot(_) => Ok(SAM_ROOT_NODE_ID),
+            TravelEvent::Push(cur_tn, cur_node_id, key) => {
+                let key = key.into();
+                self.check_node_trans(*cur_node_id, &key)?;
+                let node_id = self.insert_node_trans(*cur_node_id, key, cur_tn.is_accepting(), 0);
+                self.last_seq_end = node_id;
+                Ok(node_id)
             }
+            TravelEvent::Pop(_, cur_node_id) => Ok(cur_node_id),
         })
-        .unwrap();
     }
 
     fn topo_sort_with_queue(&mut self) {
@@ -202,7 +699,7 @@ impl<TransTable: ConstructiveTransitionTable> GeneralSam<TransTable> {
 
         self.node_pool.iter().for_each(|node| {
             node.trans.transitions().for_each(|v| {
-                in_degree[*v] += 1;
+                in_degree[v.index()] += 1;
             });
         });
         assert!(in_degree[SAM_ROOT_NODE_ID] == 0);
@@ -216,38 +713,128 @@ impl<TransTable: ConstructiveTransitionTable> GeneralSam<TransTable> {
             let u_id = self.topo_and_suf_len_sorted_order[head];
             head += 1;
             self.node_pool[u_id].trans.transitions().for_each(|v_id| {
-                in_degree[*v_id] -= 1;
-                if in_degree[*v_id] == 0 {
-                    self.topo_and_suf_len_sorted_order.push(*v_id);
+                let v_id = v_id.index();
+                in_degree[v_id] -= 1;
+                if in_degree[v_id] == 0 {
+                    self.topo_and_suf_len_sorted_order.push(v_id);
                 }
             });
         }
     }
 
     fn update_accepting(&mut self) {
+        self.node_pool
+            .iter_mut()
+            .for_each(|node| node.accept = false);
+        self.end_counts
+            .keys()
+            .for_each(|node_id| self.node_pool[*node_id].accept = true);
         self.topo_and_suf_len_sorted_order
             .iter()
             .rev()
             .for_each(|node_id| {
-                let link_id = self.node_pool[*node_id].link;
+                let link_id = self.node_pool[*node_id].link();
                 self.node_pool[link_id].accept |= self.node_pool[*node_id].accept;
             });
         self.node_pool[SAM_NIL_NODE_ID].accept = false;
+        self.node_pool[SAM_ROOT_NODE_ID].accept = self.end_counts.contains_key(&SAM_ROOT_NODE_ID);
+    }
+
+    /// Marks the end of a sequence at the node.
+    fn add_end(&mut self, node_id: GeneralSamNodeID) {
+        *self.end_counts.entry(node_id).or_default() += 1;
+    }
+
+    /// Removes one end mark of the node, if any.
+    fn remove_end(&mut self, node_id: GeneralSamNodeID) {
+        if let Entry::Occupied(mut entry) = self.end_counts.entry(node_id) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+
+    /// Marks the node as a whole prefix of the sequence `seq_id`,
+    /// which is the latest one.
+    ///
+    /// A node only having the prefix of the sequence allocating it,
+    /// which is the usual case, takes no space in the side table.
+    fn add_prefix(&mut self, node_id: GeneralSamNodeID, seq_id: usize) {
+        let (implied_seq_id, _) = self.prefix_seq_id_parts(node_id);
+        let node = &mut self.node_pool[node_id];
+        if !node.prefix {
+            node.prefix = true;
+            if self.alloc_seq_id(node_id) == seq_id {
+                return;
+            }
+        }
+        let seq_ids = self.prefix_seq_ids.entry(node_id).or_default();
+        seq_ids.extend(implied_seq_id);
+        seq_ids.push(seq_id);
     }
 
+    /// The capacity of [`TransitionTable::NodeID`] is checked
+    /// by [`GeneralSam::check_node_trans`] beforehand.
     fn alloc_node(&mut self, node: GeneralSamNode<TransTable>) -> GeneralSamNodeID {
         let id = self.node_pool.len();
         self.node_pool.push(node);
         id
     }
 
-    fn insert_node_trans<Key: Into<TransTable::KeyType>>(
+    /// Checks that the nodes allocated by [`GeneralSam::insert_node_trans`]
+    /// fit into [`TransitionTable::NodeID`], which are a new node
+    /// unless the transition exists, and a clone if a node is split.
+    ///
+    /// The suffix links are only walked when the ids are about to overflow.
+    fn check_node_trans(
+        &self,
+        last_node_id: GeneralSamNodeID,
+        key: &TransTable::KeyType,
+    ) -> Result<(), NodeIdOverflow> {
+        if NodeIdOverflow::check::<TransTable::NodeID>(self.num_of_nodes() + 2).is_ok() {
+            return Ok(());
+        }
+        let mut num_of_new_nodes = 1;
+        let mut p_node_id = last_node_id;
+        while p_node_id != SAM_NIL_NODE_ID {
+            let p_node = &self.node_pool[p_node_id];
+            if let Some(q_node_id) = p_node.trans.get_node_id(key) {
+                if p_node_id == last_node_id {
+                    num_of_new_nodes = 0;
+                }
+                if self.node_pool[q_node_id].len != p_node.len + 1 {
+                    num_of_new_nodes += 1;
+                }
+                break;
+            }
+            p_node_id = p_node.link();
+        }
+        NodeIdOverflow::check::<TransTable::NodeID>(self.num_of_nodes() + num_of_new_nodes)
+    }
+
+    /// Inserts the transition of `key` from the node,
+    /// which is checked by [`GeneralSam::check_node_trans`] beforehand.
+    fn insert_node_trans(
         &mut self,
         last_node_id: GeneralSamNodeID,
-        key: Key,
+        key: TransTable::KeyType,
         accept: bool,
+        seq_id: usize,
     ) -> GeneralSamNodeID {
-        let key: TransTable::KeyType = key.into();
+        if let Some(q_node_id) = self.node_pool[last_node_id].trans.get_node_id(&key) {
+            let target_node_id =
+                if self.node_pool[q_node_id].len == self.node_pool[last_node_id].len + 1 {
+                    q_node_id
+                } else {
+                    self.split_node(last_node_id, q_node_id, &key)
+                };
+            if accept {
+                self.add_end(target_node_id);
+            }
+            self.add_prefix(target_node_id, seq_id);
+            return target_node_id;
+        }
 
         let new_node_id = {
             let last_node = &self.node_pool[last_node_id];
@@ -257,6 +844,10 @@ impl<TransTable: ConstructiveTransitionTable> GeneralSam<TransTable> {
                 SAM_NIL_NODE_ID,
             ))
         };
+        if accept {
+            self.add_end(new_node_id);
+        }
+        self.add_prefix(new_node_id, seq_id);
 
         let mut p_node_id = last_node_id;
         while p_node_id != SAM_NIL_NODE_ID {
@@ -264,39 +855,54 @@ impl<TransTable: ConstructiveTransitionTable> GeneralSam<TransTable> {
             if p_node.trans.contains_key(&key) {
                 break;
             }
-            p_node.trans.insert(key.clone(), new_node_id);
-            p_node_id = p_node.link;
+            p_node
+                .trans
+                .insert(key.clone(), TransTable::NodeID::from_index(new_node_id));
+            p_node_id = p_node.link();
         }
 
         if p_node_id == SAM_NIL_NODE_ID {
-            self.node_pool[new_node_id].link = SAM_ROOT_NODE_ID;
+            self.node_pool[new_node_id].link = TransTable::NodeID::from_index(SAM_ROOT_NODE_ID);
             return new_node_id;
         }
 
-        let q_node_id = *self.node_pool[p_node_id].trans.get(&key).unwrap();
-        let q_node = &self.node_pool[q_node_id];
-        if q_node.len == self.node_pool[p_node_id].len + 1 {
-            self.node_pool[new_node_id].link = q_node_id;
+        let q_node_id = self.node_pool[p_node_id].trans.get_node_id(&key).unwrap();
+        if self.node_pool[q_node_id].len == self.node_pool[p_node_id].len + 1 {
+            self.node_pool[new_node_id].link = TransTable::NodeID::from_index(q_node_id);
             return new_node_id;
         }
 
-        let clone_node_id = self.alloc_node(q_node.clone());
+        let clone_node_id = self.split_node(p_node_id, q_node_id, &key);
+        self.node_pool[new_node_id].link = TransTable::NodeID::from_index(clone_node_id);
+
+        new_node_id
+    }
+
+    /// Splits the strings no longer than `len(p) + 1` out of `q`,
+    /// where `q` is the target of `p` with `key`.
+    fn split_node(
+        &mut self,
+        mut p_node_id: GeneralSamNodeID,
+        q_node_id: GeneralSamNodeID,
+        key: &TransTable::KeyType,
+    ) -> GeneralSamNodeID {
+        let clone_node_id = self.alloc_node(self.node_pool[q_node_id].clone());
         self.node_pool[clone_node_id].len = self.node_pool[p_node_id].len + 1;
+        self.node_pool[clone_node_id].prefix = false;
         while p_node_id != SAM_NIL_NODE_ID {
             let p_node = &mut self.node_pool[p_node_id];
-            if let Some(t_node_id) = p_node.trans.get_mut(&key)
-                && *t_node_id == q_node_id
+            if let Some(t_node_id) = p_node.trans.get_mut(key)
+                && t_node_id.index() == q_node_id
             {
-                *t_node_id = clone_node_id;
-                p_node_id = p_node.link;
+                *t_node_id = TransTable::NodeID::from_index(clone_node_id);
+                p_node_id = p_node.link();
                 continue;
             }
             break;
         }
 
-        self.node_pool[new_node_id].link = clone_node_id;
-        self.node_pool[q_node_id].link = clone_node_id;
+        self.node_pool[q_node_id].link = TransTable::NodeID::from_index(clone_node_id);
 
-        new_node_id
+        clone_node_id
     }
 }
@@ -0,0 +1,157 @@
+//! User-defined data attached to the nodes of a general suffix automaton.
+
+use std::ops::{Index, IndexMut};
+
+use super::{
+    GeneralSam, GeneralSamNode, GeneralSamNodeID, NodeIndex, SAM_NIL_NODE_ID, TransitionTable,
+};
+
+/// A side table holding one value for every node, indexed by node ids.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeData<T> {
+    data: Vec<T>,
+}
+
+impl<T> NodeData<T> {
+    /// Creates the table with `f` called on every node, including the nil one.
+    pub fn from_fn<
+        TransTable: TransitionTable,
+        F: FnMut(GeneralSamNodeID, &GeneralSamNode<TransTable>) -> T,
+    >(
+        sam: &GeneralSam<TransTable>,
+        mut f: F,
+    ) -> Self {
+        Self {
+            data: sam
+                .node_pool
+                .iter()
+                .enumerate()
+                .map(|(node_id, node)| f(node_id, node))
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn get(&self, node_id: GeneralSamNodeID) -> Option<&T> {
+        self.data.get(node_id)
+    }
+
+    pub fn get_mut(&mut self, node_id: GeneralSamNodeID) -> Option<&mut T> {
+        self.data.get_mut(node_id)
+    }
+
+    /// Returns an iterator over the node ids and their values.
+    pub fn iter(&self) -> impl Iterator<Item = (GeneralSamNodeID, &T)> {
+        self.data.iter().enumerate()
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Returns the value of `node_id` mutably and the one of `other_id`,
+    /// which must be different nodes.
+    fn pair_mut(&mut self, node_id: GeneralSamNodeID, other_id: GeneralSamNodeID) -> (&mut T, &T) {
+        debug_assert_ne!(node_id, other_id);
+        if node_id < other_id {
+            let (lhs, rhs) = self.data.split_at_mut(other_id);
+            (&mut lhs[node_id], &rhs[0])
+        } else {
+            let (lhs, rhs) = self.data.split_at_mut(node_id);
+            (&mut rhs[0], &lhs[other_id])
+        }
+    }
+}
+
+impl<T: Clone> NodeData<T> {
+    /// Creates the table with `value` for every node.
+    pub fn filled<TransTable: TransitionTable>(sam: &GeneralSam<TransTable>, value: T) -> Self {
+        Self {
+            data: vec![value; sam.num_of_nodes()],
+        }
+    }
+}
+
+impl<T> Index<GeneralSamNodeID> for NodeData<T> {
+    type Output = T;
+
+    fn index(&self, node_id: GeneralSamNodeID) -> &Self::Output {
+        &self.data[node_id]
+    }
+}
+
+impl<T> IndexMut<GeneralSamNodeID> for NodeData<T> {
+    fn index_mut(&mut self, node_id: GeneralSamNodeID) -> &mut Self::Output {
+        &mut self.data[node_id]
+    }
+}
+
+impl<TransTable: TransitionTable> GeneralSam<TransTable> {
+    /// Folds the values bottom-up along the suffix links,
+    /// i.e. `combine(parent, child)` is called for every node
+    /// after all of its children in the suffix link tree are combined into it.
+    ///
+    /// The values start from `init`, and the nil node is left untouched.
+    /// The nodes are sorted by their lengths for the call
+    /// if the topological order is outdated, see [`GeneralSam::refresh`].
+    pub fn fold_suffix_tree<
+        T,
+        Init: FnMut(GeneralSamNodeID, &GeneralSamNode<TransTable>) -> T,
+        Combine: FnMut(&mut T, &T),
+    >(
+        &self,
+        init: Init,
+        mut combine: Combine,
+    ) -> NodeData<T> {
+        let mut data = NodeData::from_fn(self, init);
+        self.sorted_node_ids().iter().rev().for_each(|node_id| {
+            let link_id = self.node_pool[*node_id].link();
+            if link_id != SAM_NIL_NODE_ID {
+                let (parent, child) = data.pair_mut(link_id, *node_id);
+                combine(parent, child);
+            }
+        });
+        data
+    }
+
+    /// Folds the values backwards along the transitions
+    /// in the reverse topological order,
+    /// i.e. `combine(value, key, next_value)` is called for every transition
+    /// after all of the transitions of the target node are combined into it.
+    ///
+    /// The values start from `init`, and the nil node is left untouched.
+    /// The nodes are sorted by their lengths for the call
+    /// if the topological order is outdated, see [`GeneralSam::refresh`].
+    pub fn fold_dag<
+        T,
+        Init: FnMut(GeneralSamNodeID, &GeneralSamNode<TransTable>) -> T,
+        Combine: FnMut(&mut T, TransTable::KeyType, &T),
+    >(
+        &self,
+        init: Init,
+        mut combine: Combine,
+    ) -> NodeData<T> {
+        let mut data = NodeData::from_fn(self, init);
+        self.sorted_node_ids().iter().rev().for_each(|node_id| {
+            self.node_pool[*node_id]
+                .trans
+                .iter()
+                .for_each(|(key, next_node_id)| {
+                    let (value, next_value) = data.pair_mut(*node_id, next_node_id.index());
+                    combine(value, key, next_value);
+                });
+        });
+        data
+    }
+}
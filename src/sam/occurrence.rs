@@ -0,0 +1,63 @@
+//! Enumerating occurrences along the suffix link tree.
+
+use super::{GeneralSam, GeneralSamNodeID, SuffixLinkTree, TransitionTable};
+
+impl SuffixLinkTree {
+    /// Returns an iterator over the sequence ids and the start offsets
+    /// of all occurrences of the last `len` keys fed into `node_id`,
+    /// which are the prefix nodes in the subtree of the ancestor owning `len`,
+    /// once for every sequence sharing the prefix.
+    ///
+    /// The occurrences are yielded in the depth-first search order,
+    /// use [`Iterator::take`] to cap the number of results.
+    pub fn occurrences<'s, TransTable: TransitionTable>(
+        &'s self,
+        sam: &'s GeneralSam<TransTable>,
+        node_id: GeneralSamNodeID,
+        len: usize,
+    ) -> OccurrenceIter<'s, TransTable> {
+        // shorter suffixes may occur more often in the suffix link ancestors
+        let range = self
+            .climb_while(node_id, |node_id| {
+                sam.get_node(node_id)
+                    .is_some_and(|node| len <= node.max_suffix_len())
+            })
+            .and_then(|node_id| self.get_subtree_range(node_id))
+            .unwrap_or_default();
+        OccurrenceIter {
+            sam,
+            nodes: self.get_dfs_order()[range].iter(),
+            len,
+            seq_id: None,
+            seq_ids: Default::default(),
+            end_pos: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OccurrenceIter<'s, TransTable: TransitionTable> {
+    sam: &'s GeneralSam<TransTable>,
+    nodes: std::slice::Iter<'s, GeneralSamNodeID>,
+    len: usize,
+    seq_id: Option<usize>,
+    seq_ids: std::slice::Iter<'s, usize>,
+    end_pos: usize,
+}
+
+impl<TransTable: TransitionTable> Iterator for OccurrenceIter<'_, TransTable> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(seq_id) = self.seq_id.take().or_else(|| self.seq_ids.next().copied()) {
+                return Some((seq_id, self.end_pos - self.len));
+            }
+            let node_id = *self.nodes.next()?;
+            let (seq_id, seq_ids) = self.sam.prefix_seq_id_parts(node_id);
+            self.seq_id = seq_id;
+            self.seq_ids = seq_ids.iter();
+            self.end_pos = self.sam.get_node(node_id)?.max_suffix_len();
+        }
+    }
+}
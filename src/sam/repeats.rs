@@ -0,0 +1,168 @@
+//! Repeated substrings found with the occurrence counts.
+
+use super::{GeneralSam, GeneralSamNodeID, SAM_ROOT_NODE_ID, SuffixLinkTree, TransitionTable};
+use crate::NodeIndex;
+
+/// The substrings of a node whose lengths are in `min_len..=max_len`,
+/// all of which occur `occurrence_count` times.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RepeatClass {
+    pub node_id: GeneralSamNodeID,
+    pub min_len: usize,
+    pub max_len: usize,
+    pub occurrence_count: usize,
+}
+
+/// A substring with all of its occurrences.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RepeatedSubstring {
+    pub node_id: GeneralSamNodeID,
+    pub len: usize,
+    /// Sorted sequence ids and start offsets.
+    pub positions: Vec<(usize, usize)>,
+}
+
+impl<TransTable: TransitionTable> GeneralSam<TransTable> {
+    /// Returns the classes of the substrings no shorter than `min_len`
+    /// and occurring at least `min_occurrences` times,
+    /// from the shortest to the longest maximum length.
+    ///
+    /// It yields nothing before [`GeneralSam::compute_occurrence_counts`].
+    pub fn repeats(
+        &self,
+        min_len: usize,
+        min_occurrences: usize,
+    ) -> impl Iterator<Item = RepeatClass> + '_ {
+        self.sorted_node_ids_iter()
+            .filter(|node_id| *node_id != SAM_ROOT_NODE_ID)
+            .filter_map(move |node_id| {
+                let node = &self.node_pool[node_id];
+                let occurrence_count = self.get_occurrence_count(node_id)?;
+                let repeat = RepeatClass {
+                    node_id,
+                    min_len: self.node_pool[node.link()]
+                        .len
+                        .max(min_len.saturating_sub(1))
+                        + 1,
+                    max_len: node.len,
+                    occurrence_count,
+                };
+                (occurrence_count >= min_occurrences && repeat.min_len <= repeat.max_len)
+                    .then_some(repeat)
+            })
+    }
+
+    /// Returns the longest substring occurring at least `min_occurrences`
+    /// times, with its positions listed along `tree`.
+    ///
+    /// Returns `None` if there is no such non-empty substring,
+    /// or [`GeneralSam::compute_occurrence_counts`] has not been called.
+    pub fn longest_repeated_substring(
+        &self,
+        tree: &SuffixLinkTree,
+        min_occurrences: usize,
+    ) -> Option<RepeatedSubstring> {
+        let best = self
+            .repeats(1, min_occurrences)
+            .reduce(|best, x| if x.max_len > best.max_len { x } else { best })?;
+        let mut positions: Vec<_> = tree.occurrences(self, best.node_id, best.max_len).collect();
+        positions.sort_unstable();
+        Some(RepeatedSubstring {
+            node_id: best.node_id,
+            len: best.max_len,
+            positions,
+        })
+    }
+}
+
+impl<TransTable: TransitionTable> GeneralSam<TransTable> {
+    fn sorted_node_ids_iter(&self) -> impl Iterator<Item = GeneralSamNodeID> + '_ {
+        let order = self.sorted_node_ids();
+        (0..order.len()).map(move |i| order[i])
+    }
+
+    fn repeat_class_of(&self, node_id: GeneralSamNodeID) -> Option<RepeatClass> {
+        let node = &self.node_pool[node_id];
+        let occurrence_count = self.get_occurrence_count(node_id)?;
+        (node_id != SAM_ROOT_NODE_ID && occurrence_count >= 2).then(|| RepeatClass {
+            node_id,
+            min_len: self.node_pool[node.link()].len + 1,
+            max_len: node.len,
+            occurrence_count,
+        })
+    }
+
+    fn is_right_maximal(&self, node_id: GeneralSamNodeID) -> bool {
+        let node = &self.node_pool[node_id];
+        node.accept || node.trans.transitions().nth(1).is_some()
+    }
+
+    fn is_left_maximal(&self, tree: &SuffixLinkTree, node_id: GeneralSamNodeID) -> bool {
+        self.node_pool[node_id].is_prefix() || tree.get_children(node_id).len() >= 2
+    }
+
+    /// Returns the right-maximal repeats, i.e. the repeated substrings
+    /// followed by at least two distinct keys or ends of sequences,
+    /// which are all the substrings of the yielded nodes.
+    ///
+    /// It yields nothing before [`GeneralSam::compute_occurrence_counts`],
+    /// and the ends of sequences are seen after [`GeneralSam::refresh`].
+    pub fn right_maximal_repeats(&self) -> impl Iterator<Item = RepeatClass> + '_ {
+        self.sorted_node_ids_iter()
+            .filter(|node_id| self.is_right_maximal(*node_id))
+            .filter_map(|node_id| self.repeat_class_of(node_id))
+    }
+
+    /// Returns the left-maximal repeats, i.e. the repeated substrings
+    /// preceded by at least two distinct keys or starts of sequences,
+    /// which are the longest substrings of the yielded nodes.
+    ///
+    /// It yields nothing before [`GeneralSam::compute_occurrence_counts`].
+    pub fn left_maximal_repeats<'s>(
+        &'s self,
+        tree: &'s SuffixLinkTree,
+    ) -> impl Iterator<Item = RepeatClass> + 's {
+        self.sorted_node_ids_iter()
+            .filter(|node_id| self.is_left_maximal(tree, *node_id))
+            .filter_map(|node_id| self.repeat_class_of(node_id))
+            .map(|x| RepeatClass {
+                min_len: x.max_len,
+                ..x
+            })
+    }
+
+    /// Returns the maximal repeats, which are both left-maximal
+    /// and right-maximal.
+    ///
+    /// It yields nothing before [`GeneralSam::compute_occurrence_counts`].
+    pub fn maximal_repeats<'s>(
+        &'s self,
+        tree: &'s SuffixLinkTree,
+    ) -> impl Iterator<Item = RepeatClass> + 's {
+        self.left_maximal_repeats(tree)
+            .filter(|x| self.is_right_maximal(x.node_id))
+    }
+
+    /// Returns the supermaximal repeats, i.e. the maximal repeats
+    /// which are not substrings of any other maximal repeats,
+    /// or equivalently whose extensions by one key all occur once.
+    ///
+    /// It yields nothing before [`GeneralSam::compute_occurrence_counts`].
+    pub fn supermaximal_repeats<'s>(
+        &'s self,
+        tree: &'s SuffixLinkTree,
+    ) -> impl Iterator<Item = RepeatClass> + 's {
+        let occurs_once = |node_id: GeneralSamNodeID| self.get_occurrence_count(node_id) == Some(1);
+        self.maximal_repeats(tree).filter(move |x| {
+            self.node_pool[x.node_id]
+                .trans
+                .transitions()
+                .all(|next_node_id| occurs_once(next_node_id.index()))
+                && tree
+                    .get_children(x.node_id)
+                    .iter()
+                    .copied()
+                    .all(occurs_once)
+        })
+    }
+}
@@ -0,0 +1,151 @@
+//! Deserialization of a general suffix automaton with validation.
+
+use std::collections::BTreeMap;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+use super::{GeneralSam, GeneralSamNode, GeneralSamNodeID, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID};
+use crate::{NodeIndex, TransitionTable};
+
+#[derive(Deserialize)]
+#[serde(bound = "TransTable: Deserialize<'de>, TransTable::NodeID: Deserialize<'de>")]
+struct RawGeneralSam<TransTable: TransitionTable> {
+    node_pool: Vec<GeneralSamNode<TransTable>>,
+    topo_and_suf_len_sorted_order: Vec<GeneralSamNodeID>,
+    occurrence_counts: Vec<usize>,
+    first_occurrences: Vec<Option<(usize, usize)>>,
+    seq_node_starts: Vec<GeneralSamNodeID>,
+    last_seq_end: GeneralSamNodeID,
+    end_counts: BTreeMap<GeneralSamNodeID, usize>,
+    prefix_seq_ids: BTreeMap<GeneralSamNodeID, Vec<usize>>,
+}
+
+impl<TransTable: TransitionTable> RawGeneralSam<TransTable> {
+    /// Checks that every node id is in bounds, and that the suffix links
+    /// and the transitions go to shorter and longer nodes respectively,
+    /// so neither of them forms a cycle.
+    fn validate(&self) -> Result<(), String> {
+        let num_of_nodes = self.node_pool.len();
+        if num_of_nodes < 2 {
+            return Err(format!("expected at least 2 nodes, got {num_of_nodes}"));
+        }
+        for (node_id, node) in self.node_pool.iter().enumerate() {
+            if node_id == SAM_NIL_NODE_ID {
+                if node.link() != SAM_NIL_NODE_ID
+                    || node.len != 0
+                    || node.trans.transitions().next().is_some()
+                {
+                    return Err("invalid nil node".to_owned());
+                }
+            } else if node_id == SAM_ROOT_NODE_ID {
+                if node.link() != SAM_NIL_NODE_ID || node.len != 0 {
+                    return Err("invalid root node".to_owned());
+                }
+            } else {
+                let link = self
+                    .node_pool
+                    .get(node.link())
+                    .filter(|_| node.link() != SAM_NIL_NODE_ID)
+                    .ok_or_else(|| format!("invalid suffix link of node {node_id}"))?;
+                if link.len >= node.len {
+                    return Err(format!("suffix link of node {node_id} is not shorter"));
+                }
+            }
+            for next_node_id in node.trans.transitions() {
+                let next_node_id = next_node_id.index();
+                let next_node = self
+                    .node_pool
+                    .get(next_node_id)
+                    .filter(|_| next_node_id > SAM_ROOT_NODE_ID)
+                    .ok_or_else(|| format!("invalid transition of node {node_id}"))?;
+                if next_node.len <= node.len {
+                    return Err(format!("transition of node {node_id} is not longer"));
+                }
+            }
+        }
+        if self
+            .topo_and_suf_len_sorted_order
+            .iter()
+            .any(|x| *x == SAM_NIL_NODE_ID || *x >= num_of_nodes)
+        {
+            return Err("invalid node id in topological order".to_owned());
+        }
+        if self.last_seq_end >= num_of_nodes {
+            return Err("invalid end of the latest sequence".to_owned());
+        }
+        if !self.occurrence_counts.is_empty() && self.occurrence_counts.len() != num_of_nodes {
+            return Err("invalid length of occurrence counts".to_owned());
+        }
+        if !self.first_occurrences.is_empty() && self.first_occurrences.len() != num_of_nodes {
+            return Err("invalid length of first occurrences".to_owned());
+        }
+        for (node_id, first_occurrence) in self.first_occurrences.iter().enumerate() {
+            if first_occurrence.is_some_and(|(seq_id, end_pos)| {
+                seq_id >= self.seq_node_starts.len() || end_pos < self.node_pool[node_id].len
+            }) {
+                return Err(format!("invalid first occurrence of node {node_id}"));
+            }
+        }
+        if self
+            .seq_node_starts
+            .iter()
+            .zip(self.seq_node_starts.iter().skip(1))
+            .any(|(x, y)| x > y)
+            || self
+                .seq_node_starts
+                .iter()
+                .any(|x| *x <= SAM_ROOT_NODE_ID || *x > num_of_nodes)
+        {
+            return Err("invalid starts of sequences".to_owned());
+        }
+        if self.end_counts.iter().any(|(node_id, count)| {
+            *node_id == SAM_NIL_NODE_ID || *node_id >= num_of_nodes || *count == 0
+        }) {
+            return Err("invalid ends of sequences".to_owned());
+        }
+        for (node_id, node) in self.node_pool.iter().enumerate() {
+            let valid = match self.prefix_seq_ids.get(&node_id) {
+                Some(seq_ids) => {
+                    node.prefix
+                        && !seq_ids.is_empty()
+                        && seq_ids.windows(2).all(|x| x[0] <= x[1])
+                        && seq_ids.iter().all(|x| *x < self.seq_node_starts.len())
+                }
+                None => !node.prefix || self.seq_node_starts.first().is_some_and(|x| *x <= node_id),
+            };
+            if !valid {
+                return Err(format!("invalid prefix of node {node_id}"));
+            }
+        }
+        if self
+            .prefix_seq_ids
+            .keys()
+            .any(|node_id| *node_id >= num_of_nodes)
+        {
+            return Err("invalid node id of prefixes".to_owned());
+        }
+        Ok(())
+    }
+}
+
+impl<'de, TransTable: TransitionTable + Deserialize<'de>> Deserialize<'de>
+    for GeneralSam<TransTable>
+where
+    TransTable::NodeID: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawGeneralSam::<TransTable>::deserialize(deserializer)?;
+        raw.validate().map_err(D::Error::custom)?;
+        Ok(Self {
+            node_pool: raw.node_pool,
+            topo_and_suf_len_sorted_order: raw.topo_and_suf_len_sorted_order,
+            occurrence_counts: raw.occurrence_counts,
+            first_occurrences: raw.first_occurrences,
+            seq_node_starts: raw.seq_node_starts,
+            last_seq_end: raw.last_seq_end,
+            end_counts: raw.end_counts,
+            prefix_seq_ids: raw.prefix_seq_ids,
+        })
+    }
+}
@@ -5,7 +5,10 @@ use std::marker::PhantomData;
 
 use crate::{TravelEvent, TrieNodeAlike};
 
-use super::{GeneralSam, GeneralSamNode, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID, TransitionTable};
+use super::{
+    DocumentIndex, GeneralSam, GeneralSamNode, MatchingStatistics, OccurrenceIter, SAM_NIL_NODE_ID,
+    SAM_ROOT_NODE_ID, SuffixLinkTree, TransitionTable,
+};
 
 #[derive(Debug)]
 pub struct GeneralSamState<TransTable: TransitionTable, SamRef: Borrow<GeneralSam<TransTable>>> {
@@ -75,6 +78,60 @@ impl<TransTable: TransitionTable, SamRef: Borrow<GeneralSam<TransTable>>>
             .unwrap_or(false)
     }
 
+    /// Returns the number of occurrences of the strings in the current state.
+    ///
+    /// It is available after [`GeneralSam::compute_occurrence_counts`].
+    pub fn occurrence_count(&self) -> Option<usize> {
+        self.sam.borrow().get_occurrence_count(self.node_id)
+    }
+
+    /// Returns the sequence id and the start offset
+    /// of the first occurrence of the last `len` fed keys.
+    ///
+    /// Returns `None` if the state is nil or `len` is greater than
+    /// the maximum suffix length of the state.
+    /// It is available after [`GeneralSam::compute_first_occurrences`],
+    /// see [`GeneralSam::get_first_occurrence`] for the automata
+    /// built from tries.
+    pub fn first_occurrence(&self, len: usize) -> Option<(usize, usize)> {
+        let sam = self.sam.borrow();
+        let mut node_id = self.node_id;
+        let mut node = self
+            .get_node()
+            .filter(|node| !self.is_nil() && len <= node.max_suffix_len())?;
+        // shorter suffixes may occur earlier in the suffix link ancestors
+        while let Some(parent) = sam.get_node(node.get_suffix_parent_id()).filter(|parent| {
+            node.get_suffix_parent_id() != SAM_NIL_NODE_ID && len <= parent.max_suffix_len()
+        }) {
+            node_id = node.get_suffix_parent_id();
+            node = parent;
+        }
+        let (seq_id, end_pos) = sam.get_first_occurrence(node_id)?;
+        Some((seq_id, end_pos - len))
+    }
+
+    /// Returns an iterator over the sequence ids and the start offsets
+    /// of all occurrences of the last `len` fed keys.
+    ///
+    /// See [`SuffixLinkTree::occurrences`].
+    pub fn occurrences<'s>(
+        &'s self,
+        tree: &'s SuffixLinkTree,
+        len: usize,
+    ) -> OccurrenceIter<'s, TransTable> {
+        tree.occurrences(self.sam.borrow(), self.node_id, len)
+    }
+
+    /// Returns the sorted ids of the documents containing the current state.
+    pub fn documents<'s>(&self, index: &'s DocumentIndex) -> &'s [usize] {
+        index.get_documents(self.node_id)
+    }
+
+    /// Returns the number of distinct documents containing the current state.
+    pub fn document_frequency(&self, index: &DocumentIndex) -> usize {
+        index.get_document_frequency(self.node_id)
+    }
+
     pub fn get_sam_ref(&self) -> &GeneralSam<TransTable> {
         self.sam.borrow()
     }
@@ -85,7 +142,7 @@ impl<TransTable: TransitionTable, SamRef: Borrow<GeneralSam<TransTable>>>
 
     pub fn goto_suffix_parent(&mut self) -> &mut Self {
         if let Some(node) = self.get_node() {
-            self.node_id = node.link;
+            self.node_id = node.link();
         } else {
             self.node_id = SAM_NIL_NODE_ID;
         }
@@ -93,13 +150,10 @@ impl<TransTable: TransitionTable, SamRef: Borrow<GeneralSam<TransTable>>>
     }
 
     pub fn goto<K: Borrow<TransTable::KeyType>>(&mut self, t: &K) -> &mut Self {
-        self.node_id = if let Some(next_node_id) =
-            self.get_node().and_then(|node| node.trans.get(t.borrow()))
-        {
-            *next_node_id
-        } else {
-            SAM_NIL_NODE_ID
-        };
+        self.node_id = self
+            .get_node()
+            .and_then(|node| node.trans.get_node_id(t.borrow()))
+            .unwrap_or(SAM_NIL_NODE_ID);
         self
     }
 
@@ -135,14 +189,23 @@ impl<TransTable: TransitionTable, SamRef: Borrow<GeneralSam<TransTable>> + Clone
 {
     pub fn get_non_nil_trans(&self, key: &TransTable::KeyType) -> Option<Self> {
         self.get_node()
-            .and_then(|node| node.trans.get(key))
-            .map(|x| Self {
+            .and_then(|node| node.trans.get_node_id(key))
+            .map(|node_id| Self {
                 sam: self.sam.clone(),
-                node_id: *x,
+                node_id,
                 phantom: PhantomData,
             })
     }
 
+    /// Scans `seq` against the automaton, yielding the longest match
+    /// ending at every position. See [`MatchingStatistics`].
+    pub fn matching_statistics<Seq: IntoIterator<Item = TransTable::KeyType>>(
+        self,
+        seq: Seq,
+    ) -> MatchingStatistics<TransTable, SamRef, Seq::IntoIter> {
+        MatchingStatistics::new(self, seq.into_iter())
+    }
+
     #[allow(clippy::type_complexity)]
     fn wrap_travel_along_callback<
         's,
@@ -0,0 +1,143 @@
+//! Enumerating distinct substrings in lexicographic order.
+
+use std::ops::{Bound, RangeBounds};
+
+use super::distinct::sorted_transitions;
+use super::{GeneralSam, GeneralSamNodeID, TransitionTable};
+
+/// A distinct substring yielded by [`SubstringIter`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubstringEntry<KeyType> {
+    pub keys: Vec<KeyType>,
+    pub node_id: GeneralSamNodeID,
+    /// Available after [`GeneralSam::compute_occurrence_counts`].
+    pub occurrence_count: Option<usize>,
+}
+
+/// A lazy iterator over the distinct substrings in lexicographic order,
+/// walking the transitions in key order.
+///
+/// The memory used is bounded by the maximum length of the substrings
+/// times the size of the alphabet, and the iterator can be cloned
+/// to resume from the same place later.
+#[derive(Clone, Debug)]
+pub struct SubstringIter<'s, TransTable: TransitionTable> {
+    sam: &'s GeneralSam<TransTable>,
+    min_len: usize,
+    max_len: usize,
+    keys: Vec<TransTable::KeyType>,
+    stack: Vec<std::vec::IntoIter<(TransTable::KeyType, GeneralSamNodeID)>>,
+    pending: Option<GeneralSamNodeID>,
+}
+
+impl<TransTable: TransitionTable> GeneralSam<TransTable>
+where
+    TransTable::KeyType: Ord,
+{
+    /// Returns an iterator over the distinct non-empty substrings
+    /// whose lengths are in `len_range`, in lexicographic order.
+    pub fn substrings<R: RangeBounds<usize>>(&self, len_range: R) -> SubstringIter<'_, TransTable> {
+        self.substrings_with_prefix(None, len_range)
+    }
+
+    /// Returns an iterator over the distinct non-empty substrings
+    /// starting with `prefix`, whose lengths are in `len_range`,
+    /// in lexicographic order.
+    pub fn substrings_with_prefix<
+        Iter: IntoIterator<Item = TransTable::KeyType>,
+        R: RangeBounds<usize>,
+    >(
+        &self,
+        prefix: Iter,
+        len_range: R,
+    ) -> SubstringIter<'_, TransTable> {
+        let min_len = match len_range.start_bound() {
+            Bound::Included(x) => *x,
+            Bound::Excluded(x) => x.saturating_add(1),
+            Bound::Unbounded => 0,
+        }
+        .max(1);
+        let max_len = match len_range.end_bound() {
+            Bound::Included(x) => *x,
+            Bound::Excluded(x) => x.saturating_sub(1),
+            Bound::Unbounded => usize::MAX,
+        };
+
+        let keys: Vec<_> = prefix.into_iter().collect();
+        let mut state = self.get_root_state();
+        state.feed_ref(keys.iter());
+
+        let mut res = SubstringIter {
+            sam: self,
+            min_len,
+            max_len,
+            keys,
+            stack: Vec::new(),
+            pending: None,
+        };
+        if state.is_nil() || res.keys.len() > max_len {
+            return res;
+        }
+        if res.keys.len() >= min_len {
+            res.pending = Some(state.node_id);
+        }
+        res.stack.push(res.children(state.node_id));
+        res
+    }
+}
+
+impl<TransTable: TransitionTable> SubstringIter<'_, TransTable>
+where
+    TransTable::KeyType: Ord,
+{
+    fn children(
+        &self,
+        node_id: GeneralSamNodeID,
+    ) -> std::vec::IntoIter<(TransTable::KeyType, GeneralSamNodeID)> {
+        if self.keys.len() < self.max_len {
+            sorted_transitions(self.sam, node_id).into_iter()
+        } else {
+            Vec::new().into_iter()
+        }
+    }
+
+    fn entry(&self, node_id: GeneralSamNodeID) -> SubstringEntry<TransTable::KeyType> {
+        SubstringEntry {
+            keys: self.keys.clone(),
+            node_id,
+            occurrence_count: self.sam.get_occurrence_count(node_id),
+        }
+    }
+}
+
+impl<TransTable: TransitionTable> Iterator for SubstringIter<'_, TransTable>
+where
+    TransTable::KeyType: Ord,
+{
+    type Item = SubstringEntry<TransTable::KeyType>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(node_id) = self.pending.take() {
+            return Some(self.entry(node_id));
+        }
+        loop {
+            let depth = self.stack.len();
+            match self.stack.last_mut()?.next() {
+                Some((key, node_id)) => {
+                    self.keys.push(key);
+                    let children = self.children(node_id);
+                    self.stack.push(children);
+                    if self.keys.len() >= self.min_len {
+                        return Some(self.entry(node_id));
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                    if depth > 1 {
+                        self.keys.pop();
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,448 @@
+//! A suffix automaton over a sliding window of a stream.
+
+use std::borrow::Borrow;
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+use super::{GeneralSam, GeneralSamNodeID, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID};
+use crate::{ConstructiveTransitionTable, TransitionTable};
+
+/// A suffix automaton over the last `capacity` keys of a stream.
+///
+/// The inner automaton is grown by [`GeneralSam::append`] over a buffer,
+/// which holds the window after some expired keys.
+/// Every node keeps the latest end position of its strings in the buffer,
+/// so the strings occurring only in the expired part are told apart.
+/// The buffer is rebuilt from the window once the expired keys outnumber
+/// the keys in the window, which is amortized `O(1)` per popped key
+/// and keeps the buffer within twice the size of the window.
+///
+/// The inner automaton is not exposed, since its topological order
+/// and accepting flags are not maintained and cover the expired keys,
+/// so the window is queried by [`SlidingWindowState`].
+///
+/// The latest end positions are kept on a splay-based link-cut tree,
+/// and even the read-only queries, i.e. [`SlidingWindowSam::contains`],
+/// [`SlidingWindowSam::ends_with`] and [`SlidingWindowState::goto`],
+/// splay the nodes they read through interior mutability.
+/// So the window is not [`Sync`], and cannot be queried from several
+/// threads at once, clone it for every thread or guard it by a lock instead.
+#[derive(Clone, Debug)]
+pub struct SlidingWindowSam<TransTable: ConstructiveTransitionTable> {
+    sam: GeneralSam<TransTable>,
+    buffer: VecDeque<TransTable::KeyType>,
+    num_of_expired: usize,
+    capacity: usize,
+    last_node_id: GeneralSamNodeID,
+    latest_ends: LatestEnds,
+}
+
+impl<TransTable: ConstructiveTransitionTable> SlidingWindowSam<TransTable> {
+    /// Creates an empty window holding at most `capacity` keys.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sam: GeneralSam::default(),
+            buffer: VecDeque::new(),
+            num_of_expired: 0,
+            capacity,
+            last_node_id: SAM_ROOT_NODE_ID,
+            latest_ends: LatestEnds::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of keys in the window.
+    pub fn len(&self) -> usize {
+        self.buffer.len() - self.num_of_expired
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the keys in the window.
+    pub fn iter(&self) -> impl Iterator<Item = &TransTable::KeyType> {
+        self.buffer.iter().skip(self.num_of_expired)
+    }
+
+    /// Returns the number of nodes of the inner automaton,
+    /// which is at most twice the size of the buffer plus one,
+    /// i.e. four times the size of the window plus two.
+    pub fn num_of_nodes(&self) -> usize {
+        self.sam.num_of_nodes()
+    }
+
+    /// Appends a key to the window, and returns the expired key
+    /// if the window was full.
+    pub fn push_back(&mut self, key: TransTable::KeyType) -> Option<TransTable::KeyType> {
+        if self.capacity == 0 {
+            return Some(key);
+        }
+        let expired = if self.len() >= self.capacity {
+            self.pop_front()
+        } else {
+            None
+        };
+        self.buffer.push_back(key.clone());
+        self.extend(key);
+        expired
+    }
+
+    /// Removes the oldest key of the window.
+    pub fn pop_front(&mut self) -> Option<TransTable::KeyType> {
+        if self.is_empty() {
+            return None;
+        }
+        let key = self.buffer[self.num_of_expired].clone();
+        self.num_of_expired += 1;
+        if self.num_of_expired > self.len() {
+            self.rebuild();
+        }
+        Some(key)
+    }
+
+    pub fn get_root_state(&self) -> SlidingWindowState<'_, TransTable> {
+        SlidingWindowState {
+            window: self,
+            node_id: SAM_ROOT_NODE_ID,
+            len: 0,
+        }
+    }
+
+    /// Returns whether `pattern` is a substring of the window.
+    pub fn contains<Iter: IntoIterator<Item = TransTable::KeyType>>(&self, pattern: Iter) -> bool {
+        !self.get_root_state().feed(pattern).is_nil()
+    }
+
+    /// Returns whether `pattern` is a suffix of the window.
+    pub fn ends_with<Iter: IntoIterator<Item = TransTable::KeyType>>(&self, pattern: Iter) -> bool {
+        self.get_root_state().feed(pattern).is_accepting()
+    }
+
+    fn extend(&mut self, key: TransTable::KeyType) {
+        let split_node_id = self.find_split_node_id(&key);
+        let num_of_nodes = self.sam.num_of_nodes();
+        self.last_node_id = self.sam.append(self.last_node_id, key);
+        self.latest_ends.resize(self.sam.num_of_nodes());
+
+        for node_id in num_of_nodes..self.sam.num_of_nodes() {
+            let link_id = self.sam.node_pool[node_id].link();
+            if node_id != self.last_node_id {
+                // a split moves the original node under its clone,
+                // which has all the end positions of the original node
+                let original_node_id = split_node_id.unwrap();
+                let end_pos = self.latest_ends.get(original_node_id);
+                self.latest_ends.link(node_id, link_id, end_pos);
+                self.latest_ends.cut_and_link(original_node_id, node_id);
+            } else {
+                self.latest_ends.link(node_id, link_id, 0);
+            }
+        }
+        // the new prefix and all of its suffixes end at the end of the buffer
+        self.latest_ends
+            .update_path(self.last_node_id, self.buffer.len());
+    }
+
+    /// Returns the node to be split by appending `key`,
+    /// found as [`GeneralSam::append`] does.
+    fn find_split_node_id(&self, key: &TransTable::KeyType) -> Option<GeneralSamNodeID> {
+        let mut node_id = self.last_node_id;
+        while node_id != SAM_NIL_NODE_ID {
+            let node = &self.sam.node_pool[node_id];
+            if let Some(next_node_id) = node.trans.get_node_id(key) {
+                return (self.sam.node_pool[next_node_id].len != node.len + 1)
+                    .then_some(next_node_id);
+            }
+            node_id = node.link();
+        }
+        None
+    }
+
+    fn rebuild(&mut self) {
+        let keys: Vec<_> = self.buffer.drain(..).skip(self.num_of_expired).collect();
+        self.num_of_expired = 0;
+        self.sam = GeneralSam::default();
+        self.last_node_id = SAM_ROOT_NODE_ID;
+        self.latest_ends = LatestEnds::new();
+        for key in keys {
+            self.buffer.push_back(key.clone());
+            self.extend(key);
+        }
+    }
+
+    /// Returns whether `len` keys ending in the node occur in the window.
+    fn occurs(&self, node_id: GeneralSamNodeID, len: usize) -> bool {
+        node_id != SAM_NIL_NODE_ID && self.latest_ends.get(node_id) >= self.num_of_expired + len
+    }
+
+    /// Returns whether the strings of the node are suffixes of the buffer.
+    fn is_suffix(&self, node_id: GeneralSamNodeID) -> bool {
+        node_id != SAM_NIL_NODE_ID && self.latest_ends.get(node_id) == self.buffer.len()
+    }
+}
+
+/// A state of a [`SlidingWindowSam`], which becomes nil
+/// once the fed keys are not a substring of the window.
+///
+/// Every step reads the latest end position of the next node,
+/// which takes amortized `O(log n)` time.
+pub struct SlidingWindowState<'s, TransTable: ConstructiveTransitionTable> {
+    window: &'s SlidingWindowSam<TransTable>,
+    node_id: GeneralSamNodeID,
+    len: usize,
+}
+
+impl<TransTable: ConstructiveTransitionTable> Clone for SlidingWindowState<'_, TransTable> {
+    fn clone(&self) -> Self {
+        Self {
+            window: self.window,
+            node_id: self.node_id,
+            len: self.len,
+        }
+    }
+}
+
+impl<TransTable: ConstructiveTransitionTable> std::fmt::Debug for SlidingWindowState<'_, TransTable>
+where
+    SlidingWindowSam<TransTable>: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SlidingWindowState")
+            .field("window", self.window)
+            .field("node_id", &self.node_id)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<TransTable: ConstructiveTransitionTable<KeyType = u8>> SlidingWindowState<'_, TransTable> {
+    pub fn feed_bytes<S: AsRef<[u8]>>(&mut self, seq: S) -> &mut Self {
+        self.feed_ref(seq.as_ref())
+    }
+}
+
+impl<TransTable: ConstructiveTransitionTable<KeyType = char>> SlidingWindowState<'_, TransTable> {
+    pub fn feed_chars<S: AsRef<str>>(&mut self, seq: S) -> &mut Self {
+        self.feed(seq.as_ref().chars())
+    }
+}
+
+impl<TransTable: ConstructiveTransitionTable> SlidingWindowState<'_, TransTable> {
+    pub fn is_nil(&self) -> bool {
+        self.node_id == SAM_NIL_NODE_ID
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.node_id == SAM_ROOT_NODE_ID
+    }
+
+    /// Returns whether the fed keys are a suffix of the window.
+    pub fn is_accepting(&self) -> bool {
+        self.window.is_suffix(self.node_id)
+    }
+
+    pub fn goto<K: Borrow<TransTable::KeyType>>(&mut self, t: &K) -> &mut Self {
+        self.node_id = self
+            .window
+            .sam
+            .get_node(self.node_id)
+            .and_then(|node| node.trans.get_node_id(t.borrow()))
+            .unwrap_or(SAM_NIL_NODE_ID);
+        self.len += 1;
+        if !self.window.occurs(self.node_id, self.len) {
+            self.node_id = SAM_NIL_NODE_ID;
+        }
+        self
+    }
+
+    pub fn feed<Seq: IntoIterator<Item = TransTable::KeyType>>(&mut self, seq: Seq) -> &mut Self {
+        for t in seq {
+            if self.is_nil() {
+                break;
+            }
+            self.goto(&t);
+        }
+        self
+    }
+
+    pub fn feed_ref<'k, Seq: IntoIterator<Item = &'k TransTable::KeyType>>(
+        &mut self,
+        seq: Seq,
+    ) -> &mut Self
+    where
+        <TransTable as TransitionTable>::KeyType: 'k,
+    {
+        for t in seq {
+            if self.is_nil() {
+                break;
+            }
+            self.goto(t);
+        }
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct LinkCutNode {
+    left: GeneralSamNodeID,
+    right: GeneralSamNodeID,
+    /// The parent in the splay tree, or the path parent of a splay root.
+    parent: GeneralSamNodeID,
+    end_pos: usize,
+    /// The end position to be pushed down to the splay subtree.
+    lazy_end_pos: usize,
+}
+
+/// The latest end positions of the nodes, kept on a link-cut tree
+/// over the suffix links, where the nil node is the null pointer.
+///
+/// Marking the path from a new prefix to the root and reading a node
+/// both take amortized `O(log n)` time, instead of walking
+/// all suffixes of the prefix, which are `O(n)` for periodic streams.
+/// Reading a node splays it, so the nodes are kept in cells.
+#[derive(Clone, Debug)]
+struct LatestEnds {
+    nodes: Vec<Cell<LinkCutNode>>,
+}
+
+impl LatestEnds {
+    fn new() -> Self {
+        Self {
+            nodes: vec![Cell::default(); SAM_ROOT_NODE_ID + 1],
+        }
+    }
+
+    fn resize(&mut self, num_of_nodes: usize) {
+        self.nodes.resize(num_of_nodes, Cell::default());
+    }
+
+    /// Returns the latest end position of the strings of the node.
+    fn get(&self, node_id: GeneralSamNodeID) -> usize {
+        self.splay(node_id);
+        self.node(node_id).end_pos
+    }
+
+    /// Attaches a new node below `parent_id`.
+    fn link(&self, node_id: GeneralSamNodeID, parent_id: GeneralSamNodeID, end_pos: usize) {
+        self.update(node_id, |x| {
+            x.parent = parent_id;
+            x.end_pos = end_pos;
+        });
+    }
+
+    /// Moves the subtree of the node below `parent_id`.
+    fn cut_and_link(&self, node_id: GeneralSamNodeID, parent_id: GeneralSamNodeID) {
+        self.access(node_id);
+        let left = self.node(node_id).left;
+        self.update(left, |x| x.parent = SAM_NIL_NODE_ID);
+        self.update(node_id, |x| {
+            x.left = SAM_NIL_NODE_ID;
+            x.parent = parent_id;
+        });
+    }
+
+    /// Updates the latest end positions of the node and all of its ancestors.
+    fn update_path(&self, node_id: GeneralSamNodeID, end_pos: usize) {
+        self.access(node_id);
+        self.mark(node_id, end_pos);
+    }
+
+    fn node(&self, node_id: GeneralSamNodeID) -> LinkCutNode {
+        self.nodes[node_id].get()
+    }
+
+    fn update<F: FnOnce(&mut LinkCutNode)>(&self, node_id: GeneralSamNodeID, f: F) {
+        if node_id != SAM_NIL_NODE_ID {
+            let mut node = self.node(node_id);
+            f(&mut node);
+            self.nodes[node_id].set(node);
+        }
+    }
+
+    fn mark(&self, node_id: GeneralSamNodeID, end_pos: usize) {
+        self.update(node_id, |x| {
+            x.end_pos = x.end_pos.max(end_pos);
+            x.lazy_end_pos = x.lazy_end_pos.max(end_pos);
+        });
+    }
+
+    fn push_down(&self, node_id: GeneralSamNodeID) {
+        let node = self.node(node_id);
+        if node.lazy_end_pos > 0 {
+            self.mark(node.left, node.lazy_end_pos);
+            self.mark(node.right, node.lazy_end_pos);
+            self.update(node_id, |x| x.lazy_end_pos = 0);
+        }
+    }
+
+    fn is_splay_root(&self, node_id: GeneralSamNodeID) -> bool {
+        let parent_id = self.node(node_id).parent;
+        parent_id == SAM_NIL_NODE_ID || {
+            let parent = self.node(parent_id);
+            parent.left != node_id && parent.right != node_id
+        }
+    }
+
+    fn rotate(&self, node_id: GeneralSamNodeID) {
+        let parent_id = self.node(node_id).parent;
+        let grand_id = self.node(parent_id).parent;
+        if !self.is_splay_root(parent_id) {
+            self.update(grand_id, |x| {
+                if x.left == parent_id {
+                    x.left = node_id;
+                } else {
+                    x.right = node_id;
+                }
+            });
+        }
+        self.update(node_id, |x| x.parent = grand_id);
+
+        let node = self.node(node_id);
+        let inner_id = if self.node(parent_id).left == node_id {
+            self.update(parent_id, |x| x.left = node.right);
+            self.update(node_id, |x| x.right = parent_id);
+            node.right
+        } else {
+            self.update(parent_id, |x| x.right = node.left);
+            self.update(node_id, |x| x.left = parent_id);
+            node.left
+        };
+        self.update(inner_id, |x| x.parent = parent_id);
+        self.update(parent_id, |x| x.parent = node_id);
+    }
+
+    fn splay(&self, node_id: GeneralSamNodeID) {
+        let mut path = vec![node_id];
+        while !self.is_splay_root(*path.last().unwrap()) {
+            path.push(self.node(*path.last().unwrap()).parent);
+        }
+        path.into_iter().rev().for_each(|x| self.push_down(x));
+
+        while !self.is_splay_root(node_id) {
+            let parent_id = self.node(node_id).parent;
+            if !self.is_splay_root(parent_id) {
+                let grand_id = self.node(parent_id).parent;
+                let zig_zig = (self.node(parent_id).left == node_id)
+                    == (self.node(grand_id).left == parent_id);
+                self.rotate(if zig_zig { parent_id } else { node_id });
+            }
+            self.rotate(node_id);
+        }
+    }
+
+    /// Makes the path from the root to the node a splay tree rooted at the node.
+    fn access(&self, node_id: GeneralSamNodeID) {
+        let mut last_id = SAM_NIL_NODE_ID;
+        let mut cur_id = node_id;
+        while cur_id != SAM_NIL_NODE_ID {
+            self.splay(cur_id);
+            self.update(cur_id, |x| x.right = last_id);
+            last_id = cur_id;
+            cur_id = self.node(cur_id).parent;
+        }
+        self.splay(node_id);
+    }
+}
@@ -3,22 +3,122 @@
 use std::collections::{BTreeMap, HashMap};
 use std::iter::repeat_n;
 use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::Arc;
 
 use crate::GeneralSamNodeID;
 
+/// An unsigned integer type storing node ids in transition tables,
+/// suffix links and trie parents.
+///
+/// Node ids are always exposed as [`GeneralSamNodeID`],
+/// a narrower type only shrinks the storage,
+/// at the cost of limiting the number of nodes to `MAX + 1`.
+pub trait NodeIndex:
+    Copy + Eq + Ord + std::hash::Hash + std::fmt::Debug + Default + 'static
+{
+    /// The largest node id representable by this type.
+    const MAX: GeneralSamNodeID;
+
+    fn index(self) -> GeneralSamNodeID;
+
+    /// Returns `None` if `index` exceeds [`NodeIndex::MAX`].
+    fn try_from_index(index: GeneralSamNodeID) -> Option<Self>;
+
+    /// # Panics
+    ///
+    /// Panics if `index` exceeds [`NodeIndex::MAX`].
+    fn from_index(index: GeneralSamNodeID) -> Self {
+        Self::try_from_index(index).unwrap_or_else(|| {
+            panic!(
+                "node id {index} overflows the node id type {}",
+                std::any::type_name::<Self>()
+            )
+        })
+    }
+}
+
+impl NodeIndex for usize {
+    const MAX: GeneralSamNodeID = usize::MAX;
+
+    fn index(self) -> GeneralSamNodeID {
+        self
+    }
+
+    fn try_from_index(index: GeneralSamNodeID) -> Option<Self> {
+        Some(index)
+    }
+}
+
+macro_rules! impl_node_index {
+    ($($t:ty),*) => {$(
+        impl NodeIndex for $t {
+            const MAX: GeneralSamNodeID = <$t>::MAX as GeneralSamNodeID;
+
+            fn index(self) -> GeneralSamNodeID {
+                self as GeneralSamNodeID
+            }
+
+            fn try_from_index(index: GeneralSamNodeID) -> Option<Self> {
+                Self::try_from(index).ok()
+            }
+        }
+    )*};
+}
+
+impl_node_index!(u32, u16);
+
+/// The error returned when the node ids of an automaton or a trie
+/// do not fit into the node id type of the target transition table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeIdOverflow {
+    pub num_of_nodes: usize,
+    pub max_node_id: GeneralSamNodeID,
+}
+
+impl std::fmt::Display for NodeIdOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} nodes do not fit into node ids up to {}",
+            self.num_of_nodes, self.max_node_id
+        )
+    }
+}
+
+impl std::error::Error for NodeIdOverflow {}
+
+impl NodeIdOverflow {
+    pub(crate) fn check<NodeID: NodeIndex>(num_of_nodes: usize) -> Result<(), Self> {
+        if num_of_nodes == 0 || num_of_nodes - 1 <= NodeID::MAX {
+            Ok(())
+        } else {
+            Err(Self {
+                num_of_nodes,
+                max_node_id: NodeID::MAX,
+            })
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct WithKeyDerefedIter<
     'a,
     KeyType: 'a + Clone,
-    IterType: Iterator<Item = (&'a KeyType, &'a GeneralSamNodeID)>,
+    NodeID: NodeIndex,
+    IterType: Iterator<Item = (&'a KeyType, &'a NodeID)>,
 > {
     inner: IterType,
 }
 
-impl<'a, KeyType: 'a + Clone, IterType: Iterator<Item = (&'a KeyType, &'a GeneralSamNodeID)>>
-    Iterator for WithKeyDerefedIter<'a, KeyType, IterType>
+impl<
+    'a,
+    KeyType: 'a + Clone,
+    NodeID: NodeIndex,
+    IterType: Iterator<Item = (&'a KeyType, &'a NodeID)>,
+> Iterator for WithKeyDerefedIter<'a, KeyType, NodeID, IterType>
 {
-    type Item = (KeyType, &'a GeneralSamNodeID);
+    type Item = (KeyType, &'a NodeID);
 
     fn next(&mut self) -> Option<Self::Item> {
         self.inner.next().map(|x| (x.0.clone(), x.1))
@@ -29,15 +129,16 @@ impl<'a, KeyType: 'a + Clone, IterType: Iterator<Item = (&'a KeyType, &'a Genera
 pub struct TransitionIter<
     'a,
     KeyType: 'a,
-    IterType: Iterator<Item = (KeyType, &'a GeneralSamNodeID)>,
+    NodeID: NodeIndex,
+    IterType: Iterator<Item = (KeyType, &'a NodeID)>,
 > {
     inner: IterType,
 }
 
-impl<'a, KeyType: 'a, IterType: Iterator<Item = (KeyType, &'a GeneralSamNodeID)>> Iterator
-    for TransitionIter<'a, KeyType, IterType>
+impl<'a, KeyType: 'a, NodeID: NodeIndex, IterType: Iterator<Item = (KeyType, &'a NodeID)>> Iterator
+    for TransitionIter<'a, KeyType, NodeID, IterType>
 {
-    type Item = &'a GeneralSamNodeID;
+    type Item = &'a NodeID;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.inner.next().map(|x| x.1)
@@ -46,33 +147,57 @@ impl<'a, KeyType: 'a, IterType: Iterator<Item = (KeyType, &'a GeneralSamNodeID)>
 
 pub trait TransitionTable {
     type KeyType: Clone;
-    type IterType<'a>: Iterator<Item = (Self::KeyType, &'a GeneralSamNodeID)>
+    type NodeID: NodeIndex;
+    type IterType<'a>: Iterator<Item = (Self::KeyType, &'a Self::NodeID)>
     where
         Self: 'a,
         Self::KeyType: 'a;
 
-    fn from_kv_iter<'b, Iter: IntoIterator<Item = (Self::KeyType, &'b GeneralSamNodeID)>>(
+    fn from_kv_iter<'b, Iter: IntoIterator<Item = (Self::KeyType, &'b Self::NodeID)>>(
         iter: Iter,
     ) -> Self
     where
         Self::KeyType: 'b;
-    fn get(&self, key: &Self::KeyType) -> Option<&GeneralSamNodeID>;
-    fn get_mut(&mut self, key: &Self::KeyType) -> Option<&mut GeneralSamNodeID>;
+    fn get(&self, key: &Self::KeyType) -> Option<&Self::NodeID>;
+    fn get_mut(&mut self, key: &Self::KeyType) -> Option<&mut Self::NodeID>;
     fn iter(&self) -> Self::IterType<'_>;
 
     fn contains_key(&self, key: &Self::KeyType) -> bool {
         self.get(key).is_some()
     }
 
-    fn transitions(&self) -> TransitionIter<'_, Self::KeyType, Self::IterType<'_>> {
+    /// Returns the target node id of `key` widened to [`GeneralSamNodeID`].
+    fn get_node_id(&self, key: &Self::KeyType) -> Option<GeneralSamNodeID> {
+        self.get(key).map(|x| x.index())
+    }
+
+    fn transitions(&self) -> TransitionIter<'_, Self::KeyType, Self::NodeID, Self::IterType<'_>> {
         TransitionIter { inner: self.iter() }
     }
 }
 
+/// Rebuilds `trans` as another table type, converting the node ids.
+///
+/// # Panics
+///
+/// Panics if the node ids overflow the new node id type.
+pub(crate) fn convert_trans_table<
+    Old: TransitionTable,
+    New: TransitionTable<KeyType = Old::KeyType>,
+>(
+    trans: &Old,
+) -> New {
+    let edges: Vec<_> = trans
+        .iter()
+        .map(|(k, v)| (k, New::NodeID::from_index(v.index())))
+        .collect();
+    New::from_kv_iter(edges.iter().map(|(k, v)| (k.clone(), v)))
+}
+
 pub trait ConstructiveTransitionTable: TransitionTable + Clone + Default {
-    fn insert(&mut self, key: Self::KeyType, trans: GeneralSamNodeID);
+    fn insert(&mut self, key: Self::KeyType, trans: Self::NodeID);
 
-    fn from_kv_iter<'b, Iter: IntoIterator<Item = (Self::KeyType, &'b GeneralSamNodeID)>>(
+    fn from_kv_iter<'b, Iter: IntoIterator<Item = (Self::KeyType, &'b Self::NodeID)>>(
         iter: Iter,
     ) -> Self
     where
@@ -86,31 +211,35 @@ pub trait ConstructiveTransitionTable: TransitionTable + Clone + Default {
     }
 }
 
-pub type BTreeTransTable<KeyType> = BTreeMap<KeyType, GeneralSamNodeID>;
+pub type BTreeTransTable<KeyType, NodeID = GeneralSamNodeID> = BTreeMap<KeyType, NodeID>;
 
-impl<KeyType: Ord + Clone> ConstructiveTransitionTable for BTreeTransTable<KeyType> {
-    fn insert(&mut self, key: KeyType, trans: GeneralSamNodeID) {
+impl<KeyType: Ord + Clone, NodeID: NodeIndex> ConstructiveTransitionTable
+    for BTreeTransTable<KeyType, NodeID>
+{
+    fn insert(&mut self, key: KeyType, trans: NodeID) {
         BTreeMap::insert(self, key, trans);
     }
 }
 
-impl<KeyType: Clone + Ord> TransitionTable for BTreeTransTable<KeyType> {
+impl<KeyType: Clone + Ord, NodeID: NodeIndex> TransitionTable for BTreeTransTable<KeyType, NodeID> {
     type KeyType = KeyType;
+    type NodeID = NodeID;
     type IterType<'a>
         = WithKeyDerefedIter<
         'a,
         KeyType,
-        std::collections::btree_map::Iter<'a, KeyType, GeneralSamNodeID>,
+        NodeID,
+        std::collections::btree_map::Iter<'a, KeyType, NodeID>,
     >
     where
         Self: 'a,
         Self::KeyType: 'a;
 
-    fn get(&self, key: &KeyType) -> Option<&GeneralSamNodeID> {
+    fn get(&self, key: &KeyType) -> Option<&NodeID> {
         BTreeMap::get(self, key)
     }
 
-    fn get_mut(&mut self, key: &KeyType) -> Option<&mut GeneralSamNodeID> {
+    fn get_mut(&mut self, key: &KeyType) -> Option<&mut NodeID> {
         BTreeMap::get_mut(self, key)
     }
 
@@ -120,9 +249,7 @@ impl<KeyType: Clone + Ord> TransitionTable for BTreeTransTable<KeyType> {
         }
     }
 
-    fn from_kv_iter<'b, Iter: IntoIterator<Item = (KeyType, &'b GeneralSamNodeID)>>(
-        iter: Iter,
-    ) -> Self
+    fn from_kv_iter<'b, Iter: IntoIterator<Item = (KeyType, &'b NodeID)>>(iter: Iter) -> Self
     where
         Self::KeyType: 'b,
     {
@@ -130,33 +257,37 @@ impl<KeyType: Clone + Ord> TransitionTable for BTreeTransTable<KeyType> {
     }
 }
 
-pub type HashTransTable<KeyType> = HashMap<KeyType, GeneralSamNodeID>;
+pub type HashTransTable<KeyType, NodeID = GeneralSamNodeID> = HashMap<KeyType, NodeID>;
 
-impl<KeyType: std::hash::Hash + Eq + Clone> ConstructiveTransitionTable
-    for HashTransTable<KeyType>
+impl<KeyType: std::hash::Hash + Eq + Clone, NodeID: NodeIndex> ConstructiveTransitionTable
+    for HashTransTable<KeyType, NodeID>
 {
-    fn insert(&mut self, key: KeyType, trans: GeneralSamNodeID) {
+    fn insert(&mut self, key: KeyType, trans: NodeID) {
         HashMap::insert(self, key, trans);
     }
 }
 
-impl<KeyType: std::hash::Hash + Eq + Clone> TransitionTable for HashTransTable<KeyType> {
+impl<KeyType: std::hash::Hash + Eq + Clone, NodeID: NodeIndex> TransitionTable
+    for HashTransTable<KeyType, NodeID>
+{
     type KeyType = KeyType;
+    type NodeID = NodeID;
     type IterType<'a>
         = WithKeyDerefedIter<
         'a,
         KeyType,
-        std::collections::hash_map::Iter<'a, KeyType, GeneralSamNodeID>,
+        NodeID,
+        std::collections::hash_map::Iter<'a, KeyType, NodeID>,
     >
     where
         Self: 'a,
         Self::KeyType: 'a;
 
-    fn get(&self, key: &KeyType) -> Option<&GeneralSamNodeID> {
+    fn get(&self, key: &KeyType) -> Option<&NodeID> {
         HashMap::get(self, key)
     }
 
-    fn get_mut(&mut self, key: &KeyType) -> Option<&mut GeneralSamNodeID> {
+    fn get_mut(&mut self, key: &KeyType) -> Option<&mut NodeID> {
         HashMap::get_mut(self, key)
     }
 
@@ -166,9 +297,7 @@ impl<KeyType: std::hash::Hash + Eq + Clone> TransitionTable for HashTransTable<K
         }
     }
 
-    fn from_kv_iter<'b, Iter: IntoIterator<Item = (KeyType, &'b GeneralSamNodeID)>>(
-        iter: Iter,
-    ) -> Self
+    fn from_kv_iter<'b, Iter: IntoIterator<Item = (KeyType, &'b NodeID)>>(iter: Iter) -> Self
     where
         Self::KeyType: 'b,
     {
@@ -203,21 +332,20 @@ fn bisect_unstable<K: Ord, V, C: AsRef<[(K, V)]>>(container: C, key: &K) -> Opti
 #[derive(Clone, Debug)]
 pub struct BisectTable<
     K: Clone + Ord,
-    C: AsRef<[(K, GeneralSamNodeID)]>
-        + AsMut<[(K, GeneralSamNodeID)]>
-        + FromIterator<(K, GeneralSamNodeID)>,
+    C: AsRef<[(K, NodeID)]> + AsMut<[(K, NodeID)]> + FromIterator<(K, NodeID)>,
+    NodeID: NodeIndex = GeneralSamNodeID,
 > {
     inner: C,
-    phantom: PhantomData<K>,
+    phantom: PhantomData<(K, NodeID)>,
 }
 
 #[derive(Clone, Debug)]
-pub struct BisectTableIter<'s, K: Clone + Ord> {
-    inner: core::slice::Iter<'s, (K, GeneralSamNodeID)>,
+pub struct BisectTableIter<'s, K: Clone + Ord, NodeID: NodeIndex = GeneralSamNodeID> {
+    inner: core::slice::Iter<'s, (K, NodeID)>,
 }
 
-impl<'s, K: Clone + Ord> Iterator for BisectTableIter<'s, K> {
-    type Item = (K, &'s GeneralSamNodeID);
+impl<'s, K: Clone + Ord, NodeID: NodeIndex> Iterator for BisectTableIter<'s, K, NodeID> {
+    type Item = (K, &'s NodeID);
 
     fn next(&mut self) -> Option<Self::Item> {
         self.inner.next().map(|x| (x.0.clone(), &x.1))
@@ -226,23 +354,23 @@ impl<'s, K: Clone + Ord> Iterator for BisectTableIter<'s, K> {
 
 impl<
     K: Clone + Ord,
-    C: AsRef<[(K, GeneralSamNodeID)]>
-        + AsMut<[(K, GeneralSamNodeID)]>
-        + FromIterator<(K, GeneralSamNodeID)>,
-> TransitionTable for BisectTable<K, C>
+    C: AsRef<[(K, NodeID)]> + AsMut<[(K, NodeID)]> + FromIterator<(K, NodeID)>,
+    NodeID: NodeIndex,
+> TransitionTable for BisectTable<K, C, NodeID>
 {
     type KeyType = K;
+    type NodeID = NodeID;
     type IterType<'a>
-        = BisectTableIter<'a, K>
+        = BisectTableIter<'a, K, NodeID>
     where
         Self: 'a,
         Self::KeyType: 'a;
 
-    fn get(&self, key: &Self::KeyType) -> Option<&GeneralSamNodeID> {
+    fn get(&self, key: &Self::KeyType) -> Option<&NodeID> {
         bisect_unstable(&self.inner, key).map(|i| &self.inner.as_ref()[i].1)
     }
 
-    fn get_mut(&mut self, key: &K) -> Option<&mut GeneralSamNodeID> {
+    fn get_mut(&mut self, key: &K) -> Option<&mut NodeID> {
         bisect_unstable(&self.inner, key).map(|i| &mut self.inner.as_mut()[i].1)
     }
 
@@ -252,11 +380,11 @@ impl<
         }
     }
 
-    fn from_kv_iter<'b, Iter: IntoIterator<Item = (K, &'b GeneralSamNodeID)>>(iter: Iter) -> Self
+    fn from_kv_iter<'b, Iter: IntoIterator<Item = (K, &'b NodeID)>>(iter: Iter) -> Self
     where
         Self::KeyType: 'b,
     {
-        let mut inner: Box<[(K, GeneralSamNodeID)]> =
+        let mut inner: Box<[(K, NodeID)]> =
             iter.into_iter().map(|(u, v)| (u.clone(), *v)).collect();
         inner.sort_unstable_by(|a, b| a.0.cmp(&b.0));
         Self {
@@ -266,8 +394,372 @@ impl<
     }
 }
 
-pub type VecBisectTable<K> = BisectTable<K, Vec<(K, GeneralSamNodeID)>>;
-pub type BoxBisectTable<K> = BisectTable<K, Box<[(K, GeneralSamNodeID)]>>;
+pub type VecBisectTable<K, NodeID = GeneralSamNodeID> = BisectTable<K, Vec<(K, NodeID)>, NodeID>;
+pub type BoxBisectTable<K, NodeID = GeneralSamNodeID> = BisectTable<K, Box<[(K, NodeID)]>, NodeID>;
+
+/// A transition table viewing its own range of an edge array
+/// shared by all nodes, so the transitions of a whole automaton
+/// take a single allocation, see [`crate::GeneralSam::freeze`].
+///
+/// The keys are scanned linearly in small ranges and bisected otherwise.
+///
+/// It is meant to be read only, since the shared edges are copied on write.
+/// The first write to a table sharing them takes `O(E)` time and space
+/// for the number of edges `E` of the whole automaton,
+/// and then the table owns a copy while the other tables keep sharing.
+/// Mutating a frozen automaton node by node is quadratic,
+/// alter it to a mutable table beforehand instead.
+#[derive(Clone, Debug)]
+pub struct FlatTransTable<K: Clone + Ord, NodeID: NodeIndex = GeneralSamNodeID> {
+    edges: Arc<[(K, NodeID)]>,
+    start: usize,
+    end: usize,
+}
+
+const FLAT_LINEAR_SCAN_LIMIT: usize = 8;
+
+impl<K: Clone + Ord, NodeID: NodeIndex> FlatTransTable<K, NodeID> {
+    /// Builds the tables of all nodes over one edge array,
+    /// indexed by node ids.
+    pub(crate) fn build_all<
+        'a,
+        T: TransitionTable<KeyType = K, NodeID = NodeID> + 'a,
+        Iter: IntoIterator<Item = &'a T>,
+    >(
+        tables: Iter,
+    ) -> Vec<Self> {
+        let mut edges = Vec::new();
+        let mut ranges = Vec::new();
+        for trans in tables {
+            let start = edges.len();
+            edges.extend(trans.iter().map(|(k, v)| (k, *v)));
+            edges[start..].sort_unstable_by(|a: &(K, NodeID), b| a.0.cmp(&b.0));
+            ranges.push(start..edges.len());
+        }
+        let edges: Arc<[_]> = edges.into();
+        ranges
+            .into_iter()
+            .map(|range| Self::new(edges.clone(), range))
+            .collect()
+    }
+
+    fn new(edges: Arc<[(K, NodeID)]>, range: Range<usize>) -> Self {
+        Self {
+            edges,
+            start: range.start,
+            end: range.end,
+        }
+    }
+
+    fn as_slice(&self) -> &[(K, NodeID)] {
+        &self.edges[self.start..self.end]
+    }
+
+    fn position(&self, key: &K) -> Option<usize> {
+        let edges = self.as_slice();
+        if edges.len() <= FLAT_LINEAR_SCAN_LIMIT {
+            edges.iter().position(|x| x.0 == *key)
+        } else {
+            bisect_unstable(edges, key)
+        }
+    }
+}
+
+impl<K: Clone + Ord, NodeID: NodeIndex> TransitionTable for FlatTransTable<K, NodeID> {
+    type KeyType = K;
+    type NodeID = NodeID;
+    type IterType<'a>
+        = BisectTableIter<'a, K, NodeID>
+    where
+        Self: 'a,
+        Self::KeyType: 'a;
+
+    fn get(&self, key: &K) -> Option<&NodeID> {
+        self.position(key).map(|i| &self.as_slice()[i].1)
+    }
+
+    /// Copies the shared edges on write, see [`FlatTransTable`].
+    fn get_mut(&mut self, key: &K) -> Option<&mut NodeID> {
+        let i = self.start + self.position(key)?;
+        Some(&mut Arc::make_mut(&mut self.edges)[i].1)
+    }
+
+    fn iter(&self) -> Self::IterType<'_> {
+        BisectTableIter {
+            inner: self.as_slice().iter(),
+        }
+    }
+
+    /// The table built here does not share its edges.
+    fn from_kv_iter<'b, Iter: IntoIterator<Item = (K, &'b NodeID)>>(iter: Iter) -> Self
+    where
+        Self::KeyType: 'b,
+    {
+        let mut edges: Vec<_> = iter.into_iter().map(|(k, v)| (k, *v)).collect();
+        edges.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        let len = edges.len();
+        Self::new(edges.into(), 0..len)
+    }
+}
+
+/// A key type of [`DoubleArrayTable`].
+///
+/// The codes of the keys index a lookup table
+/// spanning from the smallest code to the largest one,
+/// so they are expected to be dense, e.g. bytes or characters.
+pub trait DoubleArrayKey: Clone + Ord {
+    fn code(&self) -> usize;
+}
+
+impl DoubleArrayKey for bool {
+    fn code(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl DoubleArrayKey for u8 {
+    fn code(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl DoubleArrayKey for u16 {
+    fn code(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl DoubleArrayKey for u32 {
+    fn code(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl DoubleArrayKey for char {
+    fn code(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// The arrays shared by all nodes of a [`DoubleArrayTable`].
+///
+/// Keys are renumbered densely in their order,
+/// the transition of node `u` with key `k` is stored at
+/// slot `base[u] + k` if `check` of the slot is `u`.
+/// Free slots are checked by the nil node, which has no transitions.
+#[derive(Clone, Debug)]
+struct DoubleArray<K: DoubleArrayKey, NodeID: NodeIndex> {
+    code_offset: usize,
+    codes: Box<[u32]>,
+    keys: Box<[K]>,
+    base: Box<[usize]>,
+    check: Box<[NodeID]>,
+    next: Box<[NodeID]>,
+    edge_begin: Box<[usize]>,
+    edge_codes: Box<[u32]>,
+}
+
+impl<K: DoubleArrayKey, NodeID: NodeIndex> DoubleArray<K, NodeID> {
+    /// Places the transitions of the nodes by first fit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node ids overflow `NodeID`.
+    fn build<'a, T: TransitionTable<KeyType = K> + 'a, Iter: IntoIterator<Item = &'a T>>(
+        tables: Iter,
+    ) -> Self {
+        let edges: Vec<Vec<(K, NodeID)>> = tables
+            .into_iter()
+            .map(|trans| {
+                let mut edges: Vec<_> = trans
+                    .iter()
+                    .map(|(k, v)| (k, NodeID::from_index(v.index())))
+                    .collect();
+                edges.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+                edges
+            })
+            .collect();
+
+        let mut keys: Vec<K> = edges.iter().flatten().map(|x| x.0.clone()).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        let code_offset = keys.iter().map(|k| k.code()).min().unwrap_or(0);
+        let code_end = keys.iter().map(|k| k.code() + 1).max().unwrap_or(0);
+        let mut codes = vec![0; code_end.saturating_sub(code_offset)];
+        for (i, k) in keys.iter().enumerate() {
+            codes[k.code() - code_offset] = i as u32 + 1;
+        }
+
+        let nil = NodeID::from_index(0);
+        let mut base = vec![0; edges.len()];
+        let mut check = Vec::new();
+        let mut next = Vec::new();
+        let mut edge_begin = Vec::with_capacity(edges.len() + 1);
+        let mut edge_codes = Vec::new();
+        let mut first_free = 0;
+        for (node_id, node_edges) in edges.iter().enumerate() {
+            edge_begin.push(edge_codes.len());
+            let Some(((first_key, _), (last_key, _))) = node_edges.first().zip(node_edges.last())
+            else {
+                continue;
+            };
+            let begin = edge_codes.len();
+            edge_codes.extend(
+                node_edges
+                    .iter()
+                    .map(|(k, _)| codes[k.code() - code_offset] - 1),
+            );
+            let node_codes = &edge_codes[begin..];
+
+            while check.get(first_free).is_some_and(|x| *x != nil) {
+                first_free += 1;
+            }
+            let first_code = codes[first_key.code() - code_offset] as usize - 1;
+            let mut b = first_free.saturating_sub(first_code);
+            while node_codes
+                .iter()
+                .any(|&c| check.get(b + c as usize).is_some_and(|x| *x != nil))
+            {
+                b += 1;
+            }
+
+            let end = b + codes[last_key.code() - code_offset] as usize;
+            if check.len() < end {
+                check.resize(end, nil);
+                next.resize(end, nil);
+            }
+            let owner = NodeID::from_index(node_id);
+            for (&c, (_, target)) in node_codes.iter().zip(node_edges.iter()) {
+                check[b + c as usize] = owner;
+                next[b + c as usize] = *target;
+            }
+            base[node_id] = b;
+        }
+        edge_begin.push(edge_codes.len());
+
+        Self {
+            code_offset,
+            codes: codes.into(),
+            keys: keys.into(),
+            base: base.into(),
+            check: check.into(),
+            next: next.into(),
+            edge_begin: edge_begin.into(),
+            edge_codes: edge_codes.into(),
+        }
+    }
+
+    fn slot(&self, node_id: usize, key: &K) -> Option<usize> {
+        if self.edge_begin[node_id] == self.edge_begin[node_id + 1] {
+            return None;
+        }
+        let code = *self.codes.get(key.code().checked_sub(self.code_offset)?)?;
+        if code == 0 {
+            return None;
+        }
+        let slot = self.base[node_id] + code as usize - 1;
+        self.check
+            .get(slot)
+            .is_some_and(|x| x.index() == node_id)
+            .then_some(slot)
+    }
+}
+
+/// A transition table of a node in a double array shared by all nodes,
+/// where each transition is found by indexing in `O(1)`,
+/// see [`crate::GeneralSam::to_double_array`].
+///
+/// [`TransitionTable::from_kv_iter`] builds a double array of its own,
+/// prefer converting a whole automaton or trie at once.
+#[derive(Clone, Debug)]
+pub struct DoubleArrayTable<K: DoubleArrayKey, NodeID: NodeIndex = GeneralSamNodeID> {
+    array: Arc<DoubleArray<K, NodeID>>,
+    node_id: usize,
+}
+
+impl<K: DoubleArrayKey, NodeID: NodeIndex> DoubleArrayTable<K, NodeID> {
+    /// Builds the tables of all nodes, indexed by node ids.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node ids overflow `NodeID`.
+    pub(crate) fn build_all<
+        'a,
+        T: TransitionTable<KeyType = K> + 'a,
+        Iter: IntoIterator<Item = &'a T>,
+    >(
+        tables: Iter,
+    ) -> Vec<Self> {
+        let array = Arc::new(DoubleArray::build(tables));
+        (0..array.base.len())
+            .map(|node_id| Self {
+                array: array.clone(),
+                node_id,
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DoubleArrayTableIter<'s, K: DoubleArrayKey, NodeID: NodeIndex = GeneralSamNodeID> {
+    array: &'s DoubleArray<K, NodeID>,
+    base: usize,
+    codes: core::slice::Iter<'s, u32>,
+}
+
+impl<'s, K: DoubleArrayKey, NodeID: NodeIndex> Iterator for DoubleArrayTableIter<'s, K, NodeID> {
+    type Item = (K, &'s NodeID);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let code = *self.codes.next()? as usize;
+        Some((
+            self.array.keys[code].clone(),
+            &self.array.next[self.base + code],
+        ))
+    }
+}
+
+impl<K: DoubleArrayKey, NodeID: NodeIndex> TransitionTable for DoubleArrayTable<K, NodeID> {
+    type KeyType = K;
+    type NodeID = NodeID;
+    type IterType<'a>
+        = DoubleArrayTableIter<'a, K, NodeID>
+    where
+        Self: 'a,
+        Self::KeyType: 'a;
+
+    fn get(&self, key: &K) -> Option<&NodeID> {
+        let slot = self.array.slot(self.node_id, key)?;
+        Some(&self.array.next[slot])
+    }
+
+    /// Copies the shared double array on write,
+    /// which costs as much as copying the edges of [`FlatTransTable`].
+    fn get_mut(&mut self, key: &K) -> Option<&mut NodeID> {
+        let slot = self.array.slot(self.node_id, key)?;
+        Some(&mut Arc::make_mut(&mut self.array).next[slot])
+    }
+
+    /// The keys are iterated in order.
+    fn iter(&self) -> Self::IterType<'_> {
+        let array = &*self.array;
+        let edges = array.edge_begin[self.node_id]..array.edge_begin[self.node_id + 1];
+        DoubleArrayTableIter {
+            array,
+            base: array.base[self.node_id],
+            codes: array.edge_codes[edges].iter(),
+        }
+    }
+
+    fn from_kv_iter<'b, Iter: IntoIterator<Item = (K, &'b NodeID)>>(iter: Iter) -> Self
+    where
+        Self::KeyType: 'b,
+    {
+        let trans: BTreeTransTable<K, NodeID> = iter.into_iter().map(|(k, v)| (k, *v)).collect();
+        let mut tables = Self::build_all([&BTreeMap::new(), &trans]);
+        tables.pop().unwrap()
+    }
+}
 
 pub trait SmallAlphabet: Copy + Ord + Into<usize> {
     const SIZE_LOG_2: usize;
@@ -295,23 +787,21 @@ impl SmallAlphabet for u8 {
 #[derive(Clone, Debug)]
 pub struct WholeAlphabetTable<
     K: SmallAlphabet,
-    C: AsRef<[Option<GeneralSamNodeID>]>
-        + AsMut<[Option<GeneralSamNodeID>]>
-        + FromIterator<Option<GeneralSamNodeID>>
-        + Clone,
+    C: AsRef<[Option<NodeID>]> + AsMut<[Option<NodeID>]> + FromIterator<Option<NodeID>> + Clone,
+    NodeID: NodeIndex = GeneralSamNodeID,
 > {
     inner: C,
-    phantom: PhantomData<K>,
+    phantom: PhantomData<(K, NodeID)>,
 }
 
 #[derive(Clone, Debug)]
-pub struct WholeAlphabetTableIter<'s, K: SmallAlphabet> {
-    inner: std::iter::Enumerate<core::slice::Iter<'s, Option<GeneralSamNodeID>>>,
+pub struct WholeAlphabetTableIter<'s, K: SmallAlphabet, NodeID: NodeIndex = GeneralSamNodeID> {
+    inner: std::iter::Enumerate<core::slice::Iter<'s, Option<NodeID>>>,
     phantom: PhantomData<K>,
 }
 
-impl<'s, K: SmallAlphabet> Iterator for WholeAlphabetTableIter<'s, K> {
-    type Item = (K, &'s GeneralSamNodeID);
+impl<'s, K: SmallAlphabet, NodeID: NodeIndex> Iterator for WholeAlphabetTableIter<'s, K, NodeID> {
+    type Item = (K, &'s NodeID);
 
     fn next(&mut self) -> Option<Self::Item> {
         for (k, v) in self.inner.by_ref() {
@@ -325,11 +815,9 @@ impl<'s, K: SmallAlphabet> Iterator for WholeAlphabetTableIter<'s, K> {
 
 impl<
     K: SmallAlphabet,
-    C: AsRef<[Option<GeneralSamNodeID>]>
-        + AsMut<[Option<GeneralSamNodeID>]>
-        + FromIterator<Option<GeneralSamNodeID>>
-        + Clone,
-> Default for WholeAlphabetTable<K, C>
+    C: AsRef<[Option<NodeID>]> + AsMut<[Option<NodeID>]> + FromIterator<Option<NodeID>> + Clone,
+    NodeID: NodeIndex,
+> Default for WholeAlphabetTable<K, C, NodeID>
 {
     fn default() -> Self {
         Self {
@@ -341,13 +829,11 @@ impl<
 
 impl<
     K: SmallAlphabet,
-    C: AsRef<[Option<GeneralSamNodeID>]>
-        + AsMut<[Option<GeneralSamNodeID>]>
-        + FromIterator<Option<GeneralSamNodeID>>
-        + Clone,
-> ConstructiveTransitionTable for WholeAlphabetTable<K, C>
+    C: AsRef<[Option<NodeID>]> + AsMut<[Option<NodeID>]> + FromIterator<Option<NodeID>> + Clone,
+    NodeID: NodeIndex,
+> ConstructiveTransitionTable for WholeAlphabetTable<K, C, NodeID>
 {
-    fn insert(&mut self, key: Self::KeyType, trans: GeneralSamNodeID) {
+    fn insert(&mut self, key: Self::KeyType, trans: NodeID) {
         let k: usize = key.into();
         self.inner.as_mut()[k] = Some(trans)
     }
@@ -355,25 +841,24 @@ impl<
 
 impl<
     K: SmallAlphabet,
-    C: AsRef<[Option<GeneralSamNodeID>]>
-        + AsMut<[Option<GeneralSamNodeID>]>
-        + FromIterator<Option<GeneralSamNodeID>>
-        + Clone,
-> TransitionTable for WholeAlphabetTable<K, C>
+    C: AsRef<[Option<NodeID>]> + AsMut<[Option<NodeID>]> + FromIterator<Option<NodeID>> + Clone,
+    NodeID: NodeIndex,
+> TransitionTable for WholeAlphabetTable<K, C, NodeID>
 {
     type KeyType = K;
+    type NodeID = NodeID;
     type IterType<'a>
-        = WholeAlphabetTableIter<'a, K>
+        = WholeAlphabetTableIter<'a, K, NodeID>
     where
         Self: 'a,
         Self::KeyType: 'a;
 
-    fn get(&self, key: &Self::KeyType) -> Option<&GeneralSamNodeID> {
+    fn get(&self, key: &Self::KeyType) -> Option<&NodeID> {
         let k: usize = (*key).into();
         self.inner.as_ref().get(k).and_then(|x| x.as_ref())
     }
 
-    fn get_mut(&mut self, key: &Self::KeyType) -> Option<&mut GeneralSamNodeID> {
+    fn get_mut(&mut self, key: &Self::KeyType) -> Option<&mut NodeID> {
         let k: usize = (*key).into();
         self.inner.as_mut().get_mut(k).and_then(|x| x.as_mut())
     }
@@ -385,12 +870,80 @@ impl<
         }
     }
 
-    fn from_kv_iter<'b, Iter: IntoIterator<Item = (Self::KeyType, &'b GeneralSamNodeID)>>(
-        iter: Iter,
-    ) -> Self
+    fn from_kv_iter<'b, Iter: IntoIterator<Item = (Self::KeyType, &'b NodeID)>>(iter: Iter) -> Self
     where
         Self::KeyType: 'b,
     {
         <Self as ConstructiveTransitionTable>::from_kv_iter(iter)
     }
 }
+
+#[cfg(feature = "serde")]
+mod serialize {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{BisectTable, NodeIndex, SmallAlphabet, WholeAlphabetTable};
+
+    impl<
+        K: Clone + Ord + Serialize,
+        C: AsRef<[(K, NodeID)]> + AsMut<[(K, NodeID)]> + FromIterator<(K, NodeID)>,
+        NodeID: NodeIndex + Serialize,
+    > Serialize for BisectTable<K, C, NodeID>
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.inner.as_ref().serialize(serializer)
+        }
+    }
+
+    /// The keys are required to be strictly increasing.
+    impl<
+        'de,
+        K: Clone + Ord + Deserialize<'de>,
+        C: AsRef<[(K, NodeID)]> + AsMut<[(K, NodeID)]> + FromIterator<(K, NodeID)>,
+        NodeID: NodeIndex + Deserialize<'de>,
+    > Deserialize<'de> for BisectTable<K, C, NodeID>
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let inner = Vec::<(K, NodeID)>::deserialize(deserializer)?;
+            if inner.windows(2).any(|x| x[0].0 >= x[1].0) {
+                return Err(D::Error::custom("keys of a bisect table are not sorted"));
+            }
+            Ok(Self {
+                inner: inner.into_iter().collect(),
+                phantom: Default::default(),
+            })
+        }
+    }
+
+    impl<
+        K: SmallAlphabet,
+        C: AsRef<[Option<NodeID>]> + AsMut<[Option<NodeID>]> + FromIterator<Option<NodeID>> + Clone,
+        NodeID: NodeIndex + Serialize,
+    > Serialize for WholeAlphabetTable<K, C, NodeID>
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.inner.as_ref().serialize(serializer)
+        }
+    }
+
+    /// The table is required to cover the whole alphabet.
+    impl<
+        'de,
+        K: SmallAlphabet,
+        C: AsRef<[Option<NodeID>]> + AsMut<[Option<NodeID>]> + FromIterator<Option<NodeID>> + Clone,
+        NodeID: NodeIndex + Deserialize<'de>,
+    > Deserialize<'de> for WholeAlphabetTable<K, C, NodeID>
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let inner = Vec::<Option<NodeID>>::deserialize(deserializer)?;
+            if inner.len() != K::SIZE {
+                return Err(D::Error::invalid_length(inner.len(), &"the alphabet size"));
+            }
+            Ok(Self {
+                inner: inner.into_iter().collect(),
+                phantom: Default::default(),
+            })
+        }
+    }
+}
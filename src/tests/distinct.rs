@@ -0,0 +1,36 @@
+use std::collections::BTreeSet;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use super::{build_sam, random_strings};
+use crate::{BTreeTransTable, GeneralSam};
+
+#[test]
+fn test_count_distinct_substrings() {
+    let sam = GeneralSam::<BTreeTransTable<char>>::from_chars("abcbc");
+    assert_eq!(sam.count_distinct_substrings(), 12);
+    assert_eq!(sam.distinct_substrings_by_length(), [0, 3, 3, 3, 2, 1]);
+
+    let mut rng = StdRng::seed_from_u64(1623498172341);
+    for _ in 0..200 {
+        let strings = random_strings(&mut rng, 3, 24, 3);
+        let sam = build_sam::<BTreeTransTable<u8>>(&strings);
+        let mut substrings = BTreeSet::new();
+        for string in strings.iter() {
+            for i in 0..string.len() {
+                for j in i + 1..=string.len() {
+                    substrings.insert(string[i..j].to_vec());
+                }
+            }
+        }
+
+        assert_eq!(sam.count_distinct_substrings(), substrings.len());
+        let by_length = sam.distinct_substrings_by_length();
+        let max_len = substrings.iter().map(|x| x.len()).max().unwrap_or(0);
+        assert_eq!(by_length.len(), max_len + 1);
+        for (l, count) in by_length.iter().enumerate() {
+            assert_eq!(*count, substrings.iter().filter(|x| x.len() == l).count());
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use super::online::assert_isomorphic;
+use super::{build_sam, random_strings};
+use crate::{BTreeTransTable, DoubleArrayTable, GeneralSam, SAM_ROOT_NODE_ID, TransitionTable};
+
+#[test]
+fn test_double_array() {
+    let mut rng = StdRng::seed_from_u64(8723461029384);
+    for _ in 0..100 {
+        let sam = build_sam::<BTreeTransTable<u8>>(&random_strings(&mut rng, 3, 64, 16));
+
+        let double_array = sam.to_double_array();
+        assert_isomorphic(&sam, &double_array.alter_trans_table());
+        for node_id in 0..sam.num_of_nodes() {
+            let (node, da_node) = (
+                sam.get_node(node_id).unwrap(),
+                double_array.get_node(node_id).unwrap(),
+            );
+            assert!(TransitionTable::iter(node.get_trans()).eq(da_node.get_trans().iter()));
+            for key in u8::MIN..=u8::MAX {
+                assert_eq!(node.get_trans().get(&key), da_node.get_trans().get(&key));
+            }
+        }
+    }
+
+    let string = "a,b,c 你好🧡";
+    let sam = GeneralSam::<BTreeTransTable<char, u16>>::from_chars(string);
+    let double_array = sam.to_double_array();
+    for i in 0..string.len() {
+        for j in i + 1..=string.len() {
+            if let Some(s) = string.get(i..j) {
+                assert!(!double_array.get_root_state().feed_chars(s).is_nil());
+                assert_eq!(
+                    double_array.get_root_state().feed_chars(s).is_accepting(),
+                    string.ends_with(s)
+                );
+            }
+        }
+    }
+    assert!(double_array.get_root_state().feed_chars("你好b").is_nil());
+
+    // the shared arrays are copied on write
+    let root_trans = double_array.get_node(SAM_ROOT_NODE_ID).unwrap().get_trans();
+    let mut copied = root_trans.clone();
+    *copied.get_mut(&'好').unwrap() = 0;
+    assert_eq!(copied.get(&'好'), Some(&0));
+    assert_ne!(root_trans.get(&'好'), Some(&0));
+    assert!(!double_array.get_root_state().feed_chars("好").is_nil());
+    assert_eq!(
+        DoubleArrayTable::<char>::from_kv_iter([('b', &3), ('a', &2)])
+            .iter()
+            .collect::<Vec<_>>(),
+        [('a', &2), ('b', &3)]
+    );
+}
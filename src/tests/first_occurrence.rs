@@ -0,0 +1,53 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use super::{build_sam, random_strings};
+use crate::{BTreeTransTable, GeneralSam};
+
+#[test]
+fn test_first_occurrence() {
+    let mut rng = StdRng::seed_from_u64(5129837491827);
+    for _ in 0..200 {
+        let strings = random_strings(&mut rng, 4, 24, 3);
+        let mut sam = build_sam::<BTreeTransTable<u8>>(&strings);
+        assert!(sam.get_root_state().first_occurrence(0).is_none());
+        sam.compute_first_occurrences();
+        assert_eq!(sam.num_of_seqs(), strings.len());
+
+        for string in strings.iter() {
+            for i in 0..string.len() {
+                for j in i + 1..=string.len() {
+                    let fed = &string[i..j];
+                    let mut state = sam.get_root_state();
+                    state.feed_bytes(fed);
+                    for len in 1..=fed.len() {
+                        let pattern = &fed[fed.len() - len..];
+                        let expected = strings.iter().enumerate().find_map(|(seq_id, s)| {
+                            s.windows(pattern.len())
+                                .position(|w| w == pattern)
+                                .map(|pos| (seq_id, pos))
+                        });
+                        assert_eq!(state.first_occurrence(len), expected);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut sam = GeneralSam::<BTreeTransTable<char>>::from_chars("abcbc");
+    sam.compute_first_occurrences();
+    let mut state = sam.get_root_state();
+    state.feed_chars("bc");
+    assert_eq!(state.first_occurrence(2), Some((0, 1)));
+    assert_eq!(state.first_occurrence(1), Some((0, 2)));
+    assert_eq!(state.first_occurrence(3), None);
+    state.feed_chars("a");
+    assert_eq!(state.first_occurrence(0), None);
+
+    let mut sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes("bab");
+    sam.compute_first_occurrences();
+    let mut state = sam.get_root_state();
+    state.feed_bytes("ab");
+    assert_eq!(state.first_occurrence(2), Some((0, 1)));
+    assert_eq!(state.first_occurrence(1), Some((0, 0)));
+}
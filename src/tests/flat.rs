@@ -0,0 +1,55 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use super::online::assert_isomorphic;
+use super::{build_sam, random_strings};
+use crate::{
+    BTreeTransTable, FlatTransTable, GeneralSam, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID, TransitionTable,
+};
+
+#[test]
+fn test_freeze() {
+    let mut rng = StdRng::seed_from_u64(2290348712934);
+    for _ in 0..100 {
+        // a wide alphabet so some nodes go beyond the linear scan
+        let sam = build_sam::<BTreeTransTable<u8>>(&random_strings(&mut rng, 3, 64, 16));
+
+        let frozen = sam.freeze();
+        assert_isomorphic(&sam, &frozen.alter_trans_table());
+        for node_id in 0..sam.num_of_nodes() {
+            let (node, frozen_node) = (
+                sam.get_node(node_id).unwrap(),
+                frozen.get_node(node_id).unwrap(),
+            );
+            assert!(TransitionTable::iter(node.get_trans()).eq(frozen_node.get_trans().iter()));
+            for key in b'a'..=b'q' {
+                assert_eq!(
+                    node.get_trans().get(&key),
+                    frozen_node.get_trans().get(&key)
+                );
+            }
+            assert_eq!(
+                sam.get_state(node_id).occurrence_count(),
+                frozen.get_state(node_id).occurrence_count()
+            );
+        }
+    }
+
+    // the shared edges of the whole automaton are copied on the first write,
+    // leaving the other tables sharing the original ones
+    let sam = GeneralSam::<BTreeTransTable<char>>::from_chars("abcdefghijklmnopqrstuvwxyz");
+    let frozen = sam.freeze();
+    let root_trans = frozen.get_node(SAM_ROOT_NODE_ID).unwrap().get_trans();
+    let mut copied = root_trans.clone();
+    *copied.get_mut(&'z').unwrap() = SAM_NIL_NODE_ID;
+    assert_eq!(copied.get(&'z'), Some(&SAM_NIL_NODE_ID));
+    assert_ne!(root_trans.get(&'z'), Some(&SAM_NIL_NODE_ID));
+    assert!(copied.iter().take(25).eq(root_trans.iter().take(25)));
+    assert!(frozen.get_root_state().feed_chars("z").is_accepting());
+    assert_eq!(
+        FlatTransTable::<char>::from_kv_iter([('b', &3), ('a', &2)])
+            .iter()
+            .collect::<Vec<_>>(),
+        [('a', &2), ('b', &3)]
+    );
+}
@@ -0,0 +1,128 @@
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use super::{build_sam, random_strings};
+use crate::{
+    BTreeTransTable, FrozenGeneralSam, FrozenSamError, GeneralSam, HashTransTable, SAM_ROOT_NODE_ID,
+};
+
+#[test]
+fn test_frozen_sam() {
+    let mut rng = StdRng::seed_from_u64(6612349871234);
+    for _ in 0..100 {
+        let sam = build_sam::<HashTransTable<u8>>(&random_strings(&mut rng, 3, 24, 3));
+
+        let bytes = sam.to_frozen_bytes();
+        let frozen = FrozenGeneralSam::<u8>::from_bytes(&bytes).unwrap();
+        assert_eq!(frozen.num_of_nodes(), sam.num_of_nodes());
+        for node_id in 0..sam.num_of_nodes() {
+            let (state, frozen_state) = (sam.get_state(node_id), frozen.get_state(node_id));
+            assert_eq!(state.is_accepting(), frozen_state.is_accepting());
+            let node = state.get_node().unwrap();
+            assert_eq!(node.max_suffix_len(), frozen_state.max_suffix_len());
+            assert_eq!(
+                node.get_suffix_parent_id(),
+                frozen_state.clone().goto_suffix_parent().node_id
+            );
+            let mut trans: Vec<_> = node.get_trans().iter().map(|(k, v)| (*k, *v)).collect();
+            trans.sort();
+            assert_eq!(frozen_state.transitions().collect::<Vec<_>>(), trans);
+        }
+
+        for _ in 0..20 {
+            let len = rng.random_range(0..6);
+            let pattern: Vec<u8> = (0..len).map(|_| b'a' + rng.random_range(0..4)).collect();
+            let mut state = sam.get_root_state();
+            let mut frozen_state = frozen.get_root_state();
+            state.feed_bytes(&pattern);
+            frozen_state.feed_bytes(&pattern);
+            assert_eq!(state.node_id, frozen_state.node_id);
+            assert_eq!(state.is_nil(), frozen_state.is_nil());
+            assert_eq!(state.is_accepting(), frozen_state.is_accepting());
+        }
+    }
+}
+
+#[test]
+fn test_frozen_sam_chars() {
+    let sam = GeneralSam::<BTreeTransTable<char>>::from_chars("你好你好呀");
+    let bytes = sam.to_frozen_bytes();
+    let frozen = FrozenGeneralSam::<char>::from_bytes(&bytes).unwrap();
+    assert!(frozen.get_root_state().feed_chars("好呀").is_accepting());
+    assert!(!frozen.get_root_state().feed_chars("你好").is_accepting());
+    assert!(!frozen.get_root_state().feed_chars("你好").is_nil());
+    assert!(frozen.get_root_state().feed_chars("呀呀").is_nil());
+    assert!(frozen.get_root_state().is_root());
+
+    // an out-of-range id in the public field reads as the nil node
+    let mut state = frozen.get_root_state();
+    state.node_id = frozen.num_of_nodes();
+    assert!(!state.is_accepting());
+    assert_eq!(state.max_suffix_len(), 0);
+    assert_eq!(state.transitions().count(), 0);
+    assert!(state.clone().feed_chars("你").is_nil());
+    assert!(state.goto_suffix_parent().is_nil());
+    assert_eq!(
+        FrozenGeneralSam::<u8>::from_bytes(&bytes).unwrap_err(),
+        FrozenSamError::KeySizeMismatch {
+            expected: 1,
+            found: 4
+        }
+    );
+}
+
+fn with_checksum(mut bytes: Vec<u8>) -> Vec<u8> {
+    let checksum = bytes[40..]
+        .iter()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        });
+    bytes[32..40].copy_from_slice(&checksum.to_le_bytes());
+    bytes
+}
+
+#[test]
+fn test_frozen_sam_validation() {
+    let sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes("abcbc");
+    let bytes = sam.to_frozen_bytes();
+    let open = |bytes: &[u8]| FrozenGeneralSam::<u8>::from_bytes(bytes).err();
+    assert_eq!(open(&bytes), None);
+    assert_eq!(open(&bytes[..30]), Some(FrozenSamError::Truncated));
+    assert_eq!(
+        open(&bytes[..bytes.len() - 1]),
+        Some(FrozenSamError::Truncated)
+    );
+
+    let mut corrupted = bytes.clone();
+    corrupted[0] = b'X';
+    assert_eq!(open(&corrupted), Some(FrozenSamError::BadMagic));
+
+    let mut corrupted = bytes.clone();
+    corrupted[8] = 2;
+    assert_eq!(
+        open(&corrupted),
+        Some(FrozenSamError::UnsupportedVersion(2))
+    );
+
+    let mut corrupted = bytes.clone();
+    *corrupted.last_mut().unwrap() ^= 1;
+    assert_eq!(open(&corrupted), Some(FrozenSamError::ChecksumMismatch));
+
+    // out-of-bounds node ids are rejected even with a matching checksum
+    let num_of_nodes = sam.num_of_nodes() as u64;
+    let mut corrupted = bytes.clone();
+    let link = 40 + SAM_ROOT_NODE_ID * 32 + 8;
+    corrupted[link..link + 8].copy_from_slice(&num_of_nodes.to_le_bytes());
+    assert_eq!(
+        open(&with_checksum(corrupted)),
+        Some(FrozenSamError::InvalidNode(SAM_ROOT_NODE_ID))
+    );
+
+    let mut corrupted = bytes.clone();
+    let target = corrupted.len() - 8;
+    corrupted[target..].copy_from_slice(&num_of_nodes.to_le_bytes());
+    assert!(matches!(
+        open(&with_checksum(corrupted)),
+        Some(FrozenSamError::InvalidNode(_))
+    ));
+}
@@ -0,0 +1,119 @@
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use super::random_strings;
+use crate::{
+    BTreeTransTable, GeneralSam, IndexedSam, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID, SuffixLinkTree,
+};
+
+fn naive_ancestors(sam: &GeneralSam<BTreeTransTable<u8>>, node_id: usize) -> Vec<usize> {
+    let mut res = vec![node_id];
+    while *res.last().unwrap() != SAM_ROOT_NODE_ID {
+        let parent = sam
+            .get_node(*res.last().unwrap())
+            .unwrap()
+            .get_suffix_parent_id();
+        res.push(parent);
+    }
+    res
+}
+
+#[test]
+fn test_suffix_link_tree() {
+    let mut rng = StdRng::seed_from_u64(2938471928374);
+    for _ in 0..200 {
+        let len = rng.random_range(0..40);
+        let string: Vec<u8> = (0..len).map(|_| b'a' + rng.random_range(0..3)).collect();
+        let sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes(&string);
+        let tree = SuffixLinkTree::build(&sam);
+
+        assert_eq!(tree.get_dfs_order().len(), sam.num_of_nodes() - 1);
+        assert_eq!(tree.get_depth(SAM_NIL_NODE_ID), None);
+        assert_eq!(tree.get_parent(SAM_ROOT_NODE_ID), None);
+        assert_eq!(
+            tree.get_subtree_range(SAM_ROOT_NODE_ID),
+            Some(0..sam.num_of_nodes() - 1)
+        );
+
+        let ancestors: Vec<_> = (0..sam.num_of_nodes())
+            .map(|u| {
+                if u == SAM_NIL_NODE_ID {
+                    Vec::new()
+                } else {
+                    naive_ancestors(&sam, u)
+                }
+            })
+            .collect();
+
+        for u in SAM_ROOT_NODE_ID..sam.num_of_nodes() {
+            let node = sam.get_node(u).unwrap();
+            assert_eq!(tree.get_depth(u), Some(ancestors[u].len() - 1));
+            tree.get_children(u).iter().for_each(|&v| {
+                assert_eq!(sam.get_node(v).unwrap().get_suffix_parent_id(), u);
+                assert_eq!(tree.get_parent(v), Some(u));
+            });
+            assert_eq!(
+                tree.get_subtree_range(u).unwrap().len(),
+                ancestors.iter().filter(|x| x.contains(&u)).count()
+            );
+            assert_eq!(tree.get_dfs_order()[tree.get_entry_time(u).unwrap()], u);
+
+            for (k, &a) in ancestors[u].iter().enumerate() {
+                assert_eq!(tree.get_kth_ancestor(u, k), Some(a));
+                assert!(tree.is_ancestor(a, u));
+            }
+            assert_eq!(tree.get_kth_ancestor(u, ancestors[u].len()), None);
+
+            let min_len = rng.random_range(0..=node.max_suffix_len());
+            let expected = ancestors[u]
+                .iter()
+                .copied()
+                .take_while(|&a| sam.get_node(a).unwrap().max_suffix_len() >= min_len)
+                .last();
+            let output =
+                tree.climb_while(u, |a| sam.get_node(a).unwrap().max_suffix_len() >= min_len);
+            assert_eq!(output, expected);
+
+            let v = rng.random_range(SAM_ROOT_NODE_ID..sam.num_of_nodes());
+            let expected = ancestors[u]
+                .iter()
+                .copied()
+                .find(|a| ancestors[v].contains(a));
+            assert_eq!(tree.lca(u, v), expected);
+            assert_eq!(tree.is_ancestor(u, v), ancestors[v].contains(&u));
+        }
+    }
+}
+
+#[test]
+fn test_indexed_sam_state_of_range() {
+    let mut rng = StdRng::seed_from_u64(8172349817234);
+    for _ in 0..100 {
+        let seqs = random_strings(&mut rng, 4, 24, 3);
+        let indexed = IndexedSam::<BTreeTransTable<u8>>::from_seqs(seqs.iter().cloned());
+        let sam = indexed.get_sam();
+        let tree = indexed.get_suffix_link_tree();
+
+        assert_eq!(indexed.num_of_seqs(), seqs.len());
+        for (seq_id, seq) in seqs.iter().enumerate() {
+            assert_eq!(indexed.get_seq_len(seq_id), Some(seq.len()));
+            for l in 0..=seq.len() {
+                for r in l..=seq.len() {
+                    let mut expected = sam.get_root_state();
+                    expected.feed_bytes(&seq[l..r]);
+                    let output = indexed.state_of_range(seq_id, l, r);
+                    assert_eq!(output.node_id, expected.node_id);
+
+                    let mut output: Vec<_> = indexed.occurrences_of_range(seq_id, l, r).collect();
+                    let mut expected: Vec<_> = expected.occurrences(tree, r - l).collect();
+                    output.sort();
+                    expected.sort();
+                    assert_eq!(output, expected);
+                }
+            }
+            assert!(indexed.state_of_range(seq_id, 1, 0).is_nil());
+            assert!(indexed.state_of_range(seq_id, 0, seq.len() + 1).is_nil());
+        }
+        assert!(indexed.state_of_range(seqs.len(), 0, 0).is_nil());
+    }
+}
@@ -0,0 +1,144 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use super::{build_sam, random_strings};
+use crate::{BTreeTransTable, GeneralSam, Lz77Phrase};
+
+fn decode(strings: &[Vec<u8>], phrases: &[Lz77Phrase<u8>]) -> Vec<u8> {
+    let mut res = Vec::new();
+    for phrase in phrases.iter() {
+        match phrase {
+            Lz77Phrase::Literal(key) => res.push(*key),
+            Lz77Phrase::Copy { seq_id, start, len } => {
+                // a copy may overlap with itself when decoding the sequence alone
+                let src = strings.get(*seq_id).unwrap_or(&res).clone();
+                for i in 0..*len {
+                    let key = src
+                        .get(start + i)
+                        .copied()
+                        .unwrap_or_else(|| res[start + i]);
+                    res.push(key);
+                }
+            }
+        }
+    }
+    res
+}
+
+fn longest_prefix_in(text: &[u8], pattern: &[u8]) -> usize {
+    (0..=pattern.len())
+        .rev()
+        .find(|&l| l == 0 || text.windows(l).any(|w| w == &pattern[..l]))
+        .unwrap()
+}
+
+#[test]
+fn test_lz77_factorize() {
+    let mut rng = StdRng::seed_from_u64(5561234098123);
+    for _ in 0..300 {
+        let s = random_strings(&mut rng, 1, 40, 3).pop().unwrap();
+        let sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes(&s);
+        let phrases = sam.lz77_factorize(0, s.iter().copied()).unwrap();
+        assert_eq!(decode(&[], &phrases), s);
+        assert_eq!(sam.lz77_factorize(1, s.iter().copied()), None);
+
+        let mut pos = 0;
+        for phrase in phrases.iter() {
+            // the longest previous factor, whose source starts before it
+            let lpf = (0..pos)
+                .map(|j| {
+                    (0..s.len() - pos)
+                        .take_while(|&k| s[j + k] == s[pos + k])
+                        .count()
+                })
+                .max()
+                .unwrap_or(0);
+            match phrase {
+                Lz77Phrase::Literal(_) => assert_eq!(lpf, 0),
+                Lz77Phrase::Copy { seq_id, start, len } => {
+                    assert_eq!((*seq_id, *len), (0, lpf));
+                    assert!(*start < pos);
+                    assert_eq!(s[*start..start + len], s[pos..pos + len]);
+                }
+            }
+            pos += phrase.len();
+        }
+
+        let strings = random_strings(&mut rng, 3, 16, 3);
+        let sam = build_sam::<BTreeTransTable<u8>>(&strings);
+        // the sources are in the earlier sequences or earlier in the same one
+        for (seq_id, t) in strings.iter().enumerate() {
+            let phrases = sam.lz77_factorize(seq_id, t.iter().copied()).unwrap();
+            assert_eq!(decode(&strings[..seq_id], &phrases), *t);
+            let mut pos = 0;
+            for phrase in phrases.iter() {
+                let longest = strings[..seq_id]
+                    .iter()
+                    .map(|u| longest_prefix_in(u, &t[pos..]))
+                    .chain((0..pos).map(|j| {
+                        (0..t.len() - pos)
+                            .take_while(|&k| t[j + k] == t[pos + k])
+                            .count()
+                    }))
+                    .max()
+                    .unwrap_or(0);
+                assert_eq!(phrase.len(), longest.max(1));
+                if let Lz77Phrase::Copy {
+                    seq_id: src, start, ..
+                } = phrase
+                {
+                    assert!(*src < seq_id || (*src == seq_id && *start < pos));
+                }
+                pos += phrase.len();
+            }
+        }
+
+        let phrases = sam.relative_factorize(s.iter().copied());
+        assert_eq!(decode(&strings, &phrases), s);
+        let mut pos = 0;
+        for phrase in phrases.iter() {
+            let longest = strings
+                .iter()
+                .map(|t| longest_prefix_in(t, &s[pos..]))
+                .max()
+                .unwrap();
+            assert_eq!(phrase.len(), longest.max(1));
+            assert_eq!(matches!(phrase, Lz77Phrase::Literal(_)), longest == 0);
+            pos += phrase.len();
+        }
+    }
+
+    let sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes(b"abababc");
+    let phrases = sam.lz77_factorize(0, b"abababc".iter().copied()).unwrap();
+    assert_eq!(
+        phrases,
+        vec![
+            Lz77Phrase::Literal(b'a'),
+            Lz77Phrase::Literal(b'b'),
+            Lz77Phrase::Copy {
+                seq_id: 0,
+                start: 0,
+                len: 4
+            },
+            Lz77Phrase::Literal(b'c'),
+        ]
+    );
+
+    // the second sequence copies from the first one
+    let mut sam = GeneralSam::<BTreeTransTable<u8>>::default();
+    sam.insert_bytes("abab");
+    sam.insert_bytes("babc");
+    sam.refresh();
+    let phrases = sam.lz77_factorize(1, b"babc".iter().copied()).unwrap();
+    assert_eq!(
+        phrases,
+        vec![
+            Lz77Phrase::Copy {
+                seq_id: 0,
+                start: 1,
+                len: 3
+            },
+            Lz77Phrase::Literal(b'c'),
+        ]
+    );
+}
@@ -0,0 +1,120 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use super::random_string;
+use crate::{BTreeTransTable, GeneralSam, SAM_NIL_NODE_ID};
+
+fn is_substring(s: &[u8], pattern: &[u8]) -> bool {
+    pattern.is_empty() || s.windows(pattern.len()).any(|w| w == pattern)
+}
+
+#[test]
+fn test_matching_statistics() {
+    let mut rng = StdRng::seed_from_u64(4912873498172);
+    for _ in 0..500 {
+        let string = random_string(&mut rng, 32, 3);
+        let text = random_string(&mut rng, 32, 4);
+        let sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes(&string);
+
+        let output: Vec<_> = sam
+            .get_root_state()
+            .matching_statistics(text.iter().copied())
+            .collect();
+        assert_eq!(output.len(), text.len());
+
+        for (i, (len, state)) in output.into_iter().enumerate() {
+            let expected = (0..=i + 1)
+                .rev()
+                .find(|l| is_substring(&string, &text[i + 1 - l..=i]))
+                .unwrap();
+            assert_eq!(len, expected);
+
+            let node = state.get_node().unwrap();
+            assert!(!state.is_nil());
+            assert!(len <= node.max_suffix_len());
+            let mut fed = sam.get_root_state();
+            fed.feed_bytes(&text[i + 1 - len..=i]);
+            assert_eq!(fed.node_id, state.node_id);
+        }
+    }
+}
+
+#[test]
+fn test_matching_statistics_from_invalid_state() {
+    let sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes("abcab");
+    for node_id in [SAM_NIL_NODE_ID, 100] {
+        let mut state = sam.get_root_state();
+        state.node_id = node_id;
+        let output: Vec<_> = state
+            .matching_statistics(b"xyzab".iter().copied())
+            .map(|(len, state)| (len, state.node_id))
+            .collect();
+        let expected: Vec<_> = sam
+            .get_root_state()
+            .matching_statistics(b"xyzab".iter().copied())
+            .map(|(len, state)| (len, state.node_id))
+            .collect();
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn test_longest_common_substring() {
+    let sam = GeneralSam::<BTreeTransTable<char>>::from_chars("the quick brown fox");
+    let res = sam.longest_common_substring("a brown dog".chars()).unwrap();
+    assert_eq!((res.len, res.query_pos), (7, 1));
+    assert!(sam.get_root_state().feed_chars(" brown ").node_id == res.node_id);
+    assert!(sam.longest_common_substring("zzz".chars()).is_none());
+
+    let mut rng = StdRng::seed_from_u64(1872349817234);
+    for _ in 0..500 {
+        let string = random_string(&mut rng, 32, 3);
+        let query = random_string(&mut rng, 32, 4);
+        let sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes(&string);
+
+        let expected = (1..=query.len()).rev().find_map(|l| {
+            (0..=query.len() - l)
+                .find(|&i| is_substring(&string, &query[i..i + l]))
+                .map(|i| (l, i))
+        });
+        let output = sam
+            .longest_common_substring(query.iter().copied())
+            .map(|x| (x.len, x.query_pos));
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn test_top_k_common_substrings() {
+    let sam = GeneralSam::<BTreeTransTable<char>>::from_chars("abcdxyzabcxy");
+    let res: Vec<_> = sam
+        .top_k_common_substrings("xyabcdqxyzq".chars(), 3)
+        .into_iter()
+        .map(|x| (x.len, x.query_pos))
+        .collect();
+    assert_eq!(res, [(4, 2), (3, 7), (2, 0)]);
+
+    let mut rng = StdRng::seed_from_u64(6123987412983);
+    for _ in 0..500 {
+        let string = random_string(&mut rng, 32, 3);
+        let query = random_string(&mut rng, 32, 3);
+        let sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes(&string);
+
+        let res = sam.top_k_common_substrings(query.iter().copied(), 4);
+        assert!(res.len() <= 4);
+        assert_eq!(
+            res.first().map(|x| (x.len, x.query_pos)),
+            sam.longest_common_substring(query.iter().copied())
+                .map(|x| (x.len, x.query_pos))
+        );
+        for (i, x) in res.iter().enumerate() {
+            let matched = &query[x.query_pos..x.query_pos + x.len];
+            assert!(is_substring(&string, matched));
+            assert!(sam.get_root_state().feed_bytes(matched).node_id == x.node_id);
+            res[..i].iter().for_each(|y| {
+                assert!(y.len >= x.len);
+                assert!(&query[y.query_pos..y.query_pos + y.len] != matched);
+            });
+        }
+    }
+}
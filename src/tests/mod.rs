@@ -1,4 +1,23 @@
-use crate::{BTreeTransTable, GeneralSam};
+use rand::RngExt;
+use rand::rngs::StdRng;
+
+use crate::{BTreeTransTable, ConstructiveTransitionTable, GeneralSam};
+
+mod distinct;
+mod double_array;
+mod first_occurrence;
+mod flat;
+mod frozen;
+mod link_tree;
+mod lz77;
+mod matching;
+mod node_data;
+mod node_id;
+mod occurrence;
+mod occurrence_count;
+mod online;
+mod substring;
+mod window;
 
 #[cfg(feature = "utils")]
 mod utils;
@@ -6,6 +25,39 @@ mod utils;
 #[cfg(feature = "trie")]
 mod trie;
 
+#[cfg(feature = "serde")]
+mod serialize;
+
+/// Returns a random string shorter than `max_len`
+/// over the first `alphabet` lowercase letters.
+fn random_string(rng: &mut StdRng, max_len: usize, alphabet: u8) -> Vec<u8> {
+    let len = rng.random_range(0..max_len);
+    (0..len)
+        .map(|_| b'a' + rng.random_range(0..alphabet))
+        .collect()
+}
+
+/// Returns `1..=max_num` random strings like [`random_string`].
+fn random_strings(rng: &mut StdRng, max_num: usize, max_len: usize, alphabet: u8) -> Vec<Vec<u8>> {
+    (0..rng.random_range(1..=max_num))
+        .map(|_| random_string(rng, max_len, alphabet))
+        .collect()
+}
+
+/// Inserts `strings` in order, and refreshes the automaton
+/// with the occurrence counts computed.
+fn build_sam<TransTable: ConstructiveTransitionTable<KeyType = u8>>(
+    strings: &[Vec<u8>],
+) -> GeneralSam<TransTable> {
+    let mut sam = GeneralSam::default();
+    strings.iter().for_each(|s| {
+        sam.insert_bytes(s);
+    });
+    sam.refresh();
+    sam.compute_occurrence_counts();
+    sam
+}
+
 #[test]
 fn test_example_from_chars() {
     let sam_from_chars = GeneralSam::<BTreeTransTable<char>>::from_chars("abcbc");
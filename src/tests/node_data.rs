@@ -0,0 +1,64 @@
+use std::collections::BTreeSet;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use super::substring::{all_substrings, occurrences_of, prefix_positions};
+use super::{build_sam, random_strings};
+use crate::{BTreeTransTable, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID};
+
+#[test]
+fn test_node_data_folds() {
+    let mut rng = StdRng::seed_from_u64(3344512987123);
+    for _ in 0..200 {
+        let strings = random_strings(&mut rng, 3, 16, 3);
+        let sam = build_sam::<BTreeTransTable<u8>>(&strings);
+        let prefixes = prefix_positions(&strings);
+
+        // the sequences where the strings of every node occur
+        let seqs = sam.fold_suffix_tree(
+            |node_id, _| BTreeSet::from_iter(sam.get_prefix_seq_ids(node_id)),
+            |p, c| p.extend(c.iter().copied()),
+        );
+        let counts = sam.fold_suffix_tree(
+            |node_id, _| sam.get_prefix_seq_ids(node_id).count(),
+            |p, c| *p += c,
+        );
+        // the number of paths from every node, including the empty one
+        let paths = sam.fold_dag(|_, _| 1usize, |v, _, next| *v += next);
+        // the lexicographically largest path from every node
+        let largest = sam.fold_dag(
+            |_, _| Vec::new(),
+            |v, key, next| {
+                let path = [&[key][..], next].concat();
+                if path > *v {
+                    *v = path;
+                }
+            },
+        );
+        assert_eq!(seqs.len(), sam.num_of_nodes());
+        assert_eq!(paths[SAM_ROOT_NODE_ID], sam.count_distinct_substrings() + 1);
+
+        for s in all_substrings(&strings) {
+            let state = sam.get_root_state().feed_ref(&s).clone();
+            let occurrences = occurrences_of(&prefixes, &s);
+            assert_eq!(counts[state.node_id], occurrences.len());
+            assert_eq!(state.occurrence_count(), Some(occurrences.len()));
+            assert_eq!(
+                seqs[state.node_id],
+                occurrences.iter().map(|x| x.0).collect()
+            );
+            let expected = all_substrings(&strings)
+                .into_iter()
+                .filter_map(|t| t.strip_prefix(&s[..]).map(<[u8]>::to_vec))
+                .max()
+                .unwrap();
+            assert_eq!(largest[state.node_id], expected);
+        }
+        assert_eq!(
+            sam.fold_suffix_tree(|_, _| 0, |_, _| {})
+                .get(SAM_NIL_NODE_ID),
+            Some(&0)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use rand::distr::{Alphanumeric, SampleString};
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use super::online::assert_isomorphic;
+use crate::{
+    BTreeTransTable, GeneralSam, IterAsChain, NodeIdOverflow, SAM_ROOT_NODE_ID, VecBisectTable,
+};
+
+#[test]
+fn test_narrow_node_ids() {
+    let mut rng = StdRng::seed_from_u64(19260817);
+    for _ in 0..100 {
+        let len = rng.random_range(0..64);
+        let string = Alphanumeric.sample_string(&mut rng, len);
+
+        let mut sam = GeneralSam::<BTreeTransTable<u8, u32>>::from_bytes("");
+        let mut last = SAM_ROOT_NODE_ID;
+        for &b in string.as_bytes() {
+            last = sam.append(last, b);
+        }
+        sam.refresh();
+        let expected = GeneralSam::from_bytes(&string);
+        assert_isomorphic(&sam.alter_trans_table(), &expected);
+
+        let narrowed: GeneralSam<VecBisectTable<u8, u16>> =
+            expected.try_alter_trans_table().unwrap();
+        assert_isomorphic(&narrowed.alter_trans_table(), &expected);
+        assert_eq!(
+            narrowed.freeze().count_distinct_substrings(),
+            expected.count_distinct_substrings()
+        );
+    }
+}
+
+#[test]
+fn test_node_id_overflow() {
+    let mut rng = StdRng::seed_from_u64(20240101);
+    let string = Alphanumeric.sample_string(&mut rng, 1 << 16);
+    let sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes(string);
+    assert!(sam.num_of_nodes() > u16::MAX as usize + 1);
+
+    let err = sam
+        .try_alter_trans_table::<BTreeTransTable<u8, u16>>()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        NodeIdOverflow {
+            num_of_nodes: sam.num_of_nodes(),
+            max_node_id: u16::MAX as usize,
+        }
+    );
+    assert!(
+        sam.try_alter_trans_table::<BTreeTransTable<u8, u32>>()
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_node_id_overflow_on_construction() {
+    let mut rng = StdRng::seed_from_u64(20240101);
+    let string = Alphanumeric.sample_string(&mut rng, 1 << 16);
+    let err =
+        GeneralSam::<BTreeTransTable<u8, u16>>::try_from_trie(IterAsChain::from(string.bytes()))
+            .unwrap_err();
+    assert_eq!(err.max_node_id, u16::MAX as usize);
+
+    // the insertion stops before the key overflowing the ids,
+    // keeping the nodes of the inserted prefix without accepting it
+    let mut sam = GeneralSam::<BTreeTransTable<u8, u16>>::default();
+    assert_eq!(sam.try_insert(string.bytes()).unwrap_err(), err);
+    let len = (0..sam.num_of_nodes())
+        .filter_map(|node_id| sam.get_node(node_id))
+        .map(|node| node.max_suffix_len())
+        .max()
+        .unwrap();
+    assert!(len < string.len());
+    assert_eq!(sam.num_of_seqs(), 1);
+    sam.refresh();
+    assert!(
+        !sam.get_root_state()
+            .feed_bytes(&string[..len])
+            .is_accepting()
+    );
+
+    // inserting the prefix again reuses the nodes and accepts it
+    let num_of_nodes = sam.num_of_nodes();
+    sam.try_insert(string[..len].bytes()).unwrap();
+    assert_eq!(sam.num_of_nodes(), num_of_nodes);
+    let mut expected = GeneralSam::<BTreeTransTable<u8>>::default();
+    expected.insert_bytes(&string[..len]);
+    expected.refresh();
+    sam.refresh();
+    assert_isomorphic(&expected, &sam.alter_trans_table());
+
+    // the appending fails without changing the automaton
+    let last_node_id = sam.get_root_state().feed_bytes(&string[..len]).node_id;
+    assert_eq!(
+        sam.try_append(last_node_id, string.as_bytes()[len]),
+        Err(err)
+    );
+    assert_eq!(sam.num_of_nodes(), num_of_nodes);
+    sam.refresh();
+    assert_isomorphic(&expected, &sam.alter_trans_table());
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeSet;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use super::{build_sam, random_strings};
+use crate::{BTreeTransTable, GeneralSam, SuffixLinkTree};
+
+#[test]
+fn test_occurrences() {
+    let mut rng = StdRng::seed_from_u64(7612398471623);
+    for _ in 0..200 {
+        let strings = random_strings(&mut rng, 4, 24, 3);
+        let sam = build_sam::<BTreeTransTable<u8>>(&strings);
+        let index = SuffixLinkTree::build(&sam);
+
+        // identical prefixes are reported once for every sequence having it
+        let mut prefixes = Vec::new();
+        for (seq_id, string) in strings.iter().enumerate() {
+            for i in 1..=string.len() {
+                prefixes.push((&string[..i], seq_id));
+            }
+        }
+
+        for string in strings.iter() {
+            for i in 0..string.len() {
+                for j in i + 1..=string.len() {
+                    let pattern = &string[i..j];
+                    let expected: BTreeSet<_> = prefixes
+                        .iter()
+                        .filter(|(p, _)| p.ends_with(pattern))
+                        .map(|(p, seq_id)| (*seq_id, p.len() - pattern.len()))
+                        .collect();
+
+                    let mut state = sam.get_root_state();
+                    state.feed_bytes(pattern);
+                    let output: Vec<_> = state.occurrences(&index, pattern.len()).collect();
+                    assert_eq!(output.len(), expected.len());
+                    assert_eq!(output.into_iter().collect::<BTreeSet<_>>(), expected);
+                    assert_eq!(state.occurrence_count(), Some(expected.len()));
+
+                    let limited = state.occurrences(&index, pattern.len()).take(2).count();
+                    assert_eq!(limited, expected.len().min(2));
+
+                    // shorter suffixes are found beyond the subtree of the state
+                    for len in 0..pattern.len() {
+                        let suffix = &pattern[pattern.len() - len..];
+                        let expected: BTreeSet<_> = prefixes
+                            .iter()
+                            .filter(|(p, _)| p.ends_with(suffix))
+                            .map(|(p, seq_id)| (*seq_id, p.len() - len))
+                            .collect();
+                        let output: BTreeSet<_> = state.occurrences(&index, len).collect();
+                        assert_eq!(output, expected);
+                    }
+                }
+            }
+        }
+    }
+
+    let sam = GeneralSam::<BTreeTransTable<char>>::from_chars("abcbc");
+    let index = SuffixLinkTree::build(&sam);
+    let mut state = sam.get_root_state();
+    state.feed_chars("bc");
+    let mut output: Vec<_> = state.occurrences(&index, 2).collect();
+    output.sort();
+    assert_eq!(output, [(0, 1), (0, 3)]);
+    assert_eq!(state.occurrences(&index, 3).count(), 0);
+
+    let sam = GeneralSam::<BTreeTransTable<char>>::from_chars("abcab");
+    let index = SuffixLinkTree::build(&sam);
+    let mut state = sam.get_root_state();
+    state.feed_chars("cab");
+    let mut output: Vec<_> = state.occurrences(&index, 1).collect();
+    output.sort();
+    assert_eq!(output, [(0, 1), (0, 4)]);
+
+    let mut sam = GeneralSam::<BTreeTransTable<char>>::default();
+    sam.insert_chars("abc");
+    sam.insert_chars("abd");
+    sam.refresh();
+    sam.compute_occurrence_counts();
+    let index = SuffixLinkTree::build(&sam);
+    let mut state = sam.get_root_state();
+    state.feed_chars("b");
+    let mut output: Vec<_> = state.occurrences(&index, 1).collect();
+    output.sort();
+    assert_eq!(output, [(0, 1), (1, 1)]);
+    assert_eq!(state.occurrence_count(), Some(2));
+    assert_eq!(
+        sam.get_prefix_seq_ids(state.node_id).collect::<Vec<_>>(),
+        [0, 1]
+    );
+}
@@ -0,0 +1,61 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use super::random_string;
+use crate::{BTreeTransTable, GeneralSam, SubstringRank};
+
+#[test]
+fn test_occurrence_count() {
+    let mut rng = StdRng::seed_from_u64(8712398471293);
+    for _ in 0..200 {
+        let string = random_string(&mut rng, 48, 3);
+        let len = string.len();
+
+        let mut sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes(&string);
+        assert!(sam.get_root_state().occurrence_count().is_none());
+        sam.compute_occurrence_counts();
+
+        for i in 0..len {
+            for j in i + 1..=len.min(i + 6) {
+                let pattern = &string[i..j];
+                let expected = string
+                    .windows(pattern.len())
+                    .filter(|w| *w == pattern)
+                    .count();
+                let mut state = sam.get_root_state();
+                state.feed_bytes(pattern);
+                assert_eq!(state.occurrence_count(), Some(expected));
+            }
+        }
+        assert_eq!(
+            sam.get_root_state().feed_bytes("d").occurrence_count(),
+            Some(0)
+        );
+    }
+}
+
+#[test]
+fn test_occurrence_count_before_refresh() {
+    // the folds sort the nodes by their lengths without the topological order
+    let mut sam = GeneralSam::<BTreeTransTable<u8>>::default();
+    sam.insert_bytes("aaaa");
+    sam.compute_occurrence_counts();
+    assert_eq!(
+        sam.get_root_state().feed_bytes("a").occurrence_count(),
+        Some(4)
+    );
+    assert_eq!(sam.repeats(1, 2).map(|x| x.max_len).max(), Some(3));
+    assert_eq!(SubstringRank::build(&sam).num_of_substrings(), 4);
+
+    // the counts are outdated by an insertion until refreshed
+    sam.insert_bytes("ab");
+    assert_eq!(
+        sam.get_root_state().feed_bytes("a").occurrence_count(),
+        None
+    );
+    sam.refresh();
+    assert_eq!(
+        sam.get_root_state().feed_bytes("a").occurrence_count(),
+        Some(5)
+    );
+}
@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+
+use rand::distr::{Alphanumeric, SampleString};
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use crate::{BTreeTransTable, GeneralSam, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID};
+
+pub(super) fn assert_isomorphic<K: Clone + Ord + std::fmt::Debug>(
+    lhs: &GeneralSam<BTreeTransTable<K>>,
+    rhs: &GeneralSam<BTreeTransTable<K>>,
+) {
+    assert_eq!(
+        lhs.get_topo_and_suf_len_sorted_node_ids().len(),
+        rhs.get_topo_and_suf_len_sorted_node_ids().len()
+    );
+
+    let mut mapping = vec![SAM_NIL_NODE_ID; lhs.num_of_nodes()];
+    mapping[SAM_ROOT_NODE_ID] = SAM_ROOT_NODE_ID;
+
+    let mut queue = VecDeque::from([SAM_ROOT_NODE_ID]);
+    while let Some(u) = queue.pop_front() {
+        let (lhs_node, rhs_node) = (lhs.get_node(u).unwrap(), rhs.get_node(mapping[u]).unwrap());
+        assert_eq!(lhs_node.is_accepting(), rhs_node.is_accepting());
+        assert_eq!(lhs_node.max_suffix_len(), rhs_node.max_suffix_len());
+        assert_eq!(
+            lhs_node.get_trans().keys().collect::<Vec<_>>(),
+            rhs_node.get_trans().keys().collect::<Vec<_>>()
+        );
+        for (key, v) in lhs_node.get_trans().iter() {
+            let w = *rhs_node.get_trans().get(key).unwrap();
+            if mapping[*v] == SAM_NIL_NODE_ID {
+                mapping[*v] = w;
+                queue.push_back(*v);
+            } else {
+                assert_eq!(mapping[*v], w);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_append_chars() {
+    let mut sam = GeneralSam::<BTreeTransTable<char>>::default();
+    let mut last = SAM_ROOT_NODE_ID;
+    for c in "abcbc".chars() {
+        last = sam.append(last, c);
+    }
+    sam.refresh();
+
+    assert!(sam.get_root_state().feed_chars("cbc").is_accepting());
+    assert!(!sam.get_root_state().feed_chars("bcb").is_accepting());
+    assert!(!sam.get_root_state().feed_chars("bcb").is_nil());
+    assert!(sam.get_root_state().feed_chars("abcbc").node_id == last);
+    assert!(!sam.get_root_state().is_accepting());
+
+    assert_isomorphic(&sam, &GeneralSam::from_chars("abcbc"));
+}
+
+#[test]
+fn test_default_accepts_empty_string() {
+    let sam = GeneralSam::<BTreeTransTable<u8>>::default();
+    assert!(sam.get_root_state().is_accepting());
+    assert!(!sam.get_state(SAM_NIL_NODE_ID).is_accepting());
+
+    let mut sam = sam;
+    sam.insert_bytes("ab");
+    sam.refresh();
+    assert!(!sam.get_root_state().is_accepting());
+    assert!(sam.get_root_state().feed_bytes("b").is_accepting());
+}
+
+#[test]
+fn test_append_keeps_other_ends() {
+    let mut sam = GeneralSam::<BTreeTransTable<u8>>::default();
+    sam.insert_bytes("ab");
+    let mut last = SAM_ROOT_NODE_ID;
+    for b in "abc".bytes() {
+        last = sam.append(last, b);
+    }
+    sam.refresh();
+
+    assert!(sam.get_root_state().feed_bytes("ab").is_accepting());
+    assert!(sam.get_root_state().feed_bytes("abc").is_accepting());
+    assert!(sam.get_root_state().feed_bytes("bc").is_accepting());
+    assert!(!sam.get_root_state().feed_bytes("a").is_accepting());
+
+    // the mark of the appended sequence moves, the inserted one stays
+    sam.append(last, b'd');
+    sam.refresh();
+    assert!(sam.get_root_state().feed_bytes("ab").is_accepting());
+    assert!(!sam.get_root_state().feed_bytes("abc").is_accepting());
+    assert!(sam.get_root_state().feed_bytes("abcd").is_accepting());
+}
+
+#[test]
+fn test_append_to_latest_sequence() {
+    let mut sam = GeneralSam::<BTreeTransTable<u8>>::default();
+    sam.insert_bytes("aaaa");
+    let b_end = sam.insert_bytes("b");
+    let c_end = sam.append(b_end, b'c');
+    sam.refresh();
+    sam.compute_first_occurrences();
+
+    let mut state = sam.get_root_state();
+    state.feed_bytes("bc");
+    assert_eq!(state.node_id, c_end);
+    assert_eq!(state.first_occurrence(2), Some((1, 0)));
+    assert!(!sam.get_root_state().feed_bytes("b").is_accepting());
+
+    // the root starts a new sequence
+    let d_end = sam.append(SAM_ROOT_NODE_ID, b'd');
+    sam.refresh();
+    assert_eq!(sam.num_of_seqs(), 3);
+    let mut state = sam.get_root_state();
+    state.feed_bytes("d");
+    assert_eq!(state.node_id, d_end);
+    assert_eq!(state.first_occurrence(1), Some((2, 0)));
+    assert!(sam.get_root_state().feed_bytes("bc").is_accepting());
+}
+
+#[test]
+#[should_panic(expected = "neither the end of the latest sequence nor the root")]
+fn test_append_to_earlier_sequence() {
+    let mut sam = GeneralSam::<BTreeTransTable<u8>>::default();
+    let a_end = sam.insert_bytes("aaaa");
+    sam.insert_bytes("b");
+    sam.append(a_end, b'c');
+}
+
+#[test]
+fn test_append_random_bytes() {
+    let mut rng = StdRng::seed_from_u64(2398471293847);
+    for _ in 0..1000 {
+        let len = rng.random_range(0..64);
+        let string = Alphanumeric.sample_string(&mut rng, len);
+        let split = rng.random_range(0..=len);
+
+        let mut sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes(&string[..split]);
+        let mut last = sam.get_root_state().feed_bytes(&string[..split]).node_id;
+        for &b in &string.as_bytes()[split..] {
+            last = sam.append(last, b);
+        }
+        sam.refresh();
+
+        assert_isomorphic(&sam, &GeneralSam::from_bytes(&string));
+    }
+}
+
+#[test]
+fn test_insert_bytes() {
+    let mut sam = GeneralSam::<BTreeTransTable<u8>>::default();
+    sam.insert_bytes("abcbc");
+    sam.refresh();
+    assert_isomorphic(&sam, &GeneralSam::from_bytes("abcbc"));
+
+    sam.insert_bytes("bcd");
+    sam.insert_bytes("abc");
+    sam.refresh();
+    assert!(sam.get_root_state().feed_bytes("bc").is_accepting());
+    assert!(sam.get_root_state().feed_bytes("abc").is_accepting());
+    assert!(sam.get_root_state().feed_bytes("cd").is_accepting());
+    assert!(!sam.get_root_state().feed_bytes("cb").is_accepting());
+    assert!(!sam.get_root_state().feed_bytes("cb").is_nil());
+    assert!(sam.get_root_state().feed_bytes("abcd").is_nil());
+}
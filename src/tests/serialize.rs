@@ -0,0 +1,138 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use super::online::assert_isomorphic;
+use crate::{
+    BTreeTransTable, BoxBisectTable, GeneralSam, GeneralSamNodeID, HashTransTable, TransitionTable,
+    VecBisectTable, WholeAlphabetTable,
+};
+
+fn build_sam() -> GeneralSam<BTreeTransTable<u8>> {
+    let mut sam = GeneralSam::default();
+    sam.insert_bytes("abcbc");
+    sam.insert_bytes("cbcab");
+    sam.insert_bytes("bcc");
+    sam.refresh();
+    sam.compute_occurrence_counts();
+    sam.compute_first_occurrences();
+    sam
+}
+
+fn round_trip<TransTable: TransitionTable<KeyType = u8>>(sam: &GeneralSam<BTreeTransTable<u8>>)
+where
+    GeneralSam<TransTable>: Serialize + DeserializeOwned,
+{
+    let altered: GeneralSam<TransTable> = sam.alter_trans_table();
+    let json = serde_json::to_string(&altered).unwrap();
+    let loaded: GeneralSam<TransTable> = serde_json::from_str(&json).unwrap();
+    let loaded: GeneralSam<BTreeTransTable<u8>> = loaded.alter_trans_table();
+    assert_isomorphic(sam, &loaded);
+    assert_eq!(
+        sam.get_topo_and_suf_len_sorted_node_ids(),
+        loaded.get_topo_and_suf_len_sorted_node_ids()
+    );
+    assert_eq!(sam.num_of_seqs(), loaded.num_of_seqs());
+    for node_id in 0..sam.num_of_nodes() {
+        let (lhs, rhs) = (sam.get_state(node_id), loaded.get_state(node_id));
+        assert_eq!(lhs.occurrence_count(), rhs.occurrence_count());
+        assert_eq!(lhs.first_occurrence(1), rhs.first_occurrence(1));
+    }
+}
+
+#[test]
+fn test_serde_round_trip() {
+    let sam = build_sam();
+    round_trip::<BTreeTransTable<u8>>(&sam);
+    round_trip::<HashTransTable<u8>>(&sam);
+    round_trip::<VecBisectTable<u8>>(&sam);
+    round_trip::<BoxBisectTable<u8>>(&sam);
+    round_trip::<WholeAlphabetTable<u8, Vec<Option<GeneralSamNodeID>>>>(&sam);
+
+    let loaded: GeneralSam<BTreeTransTable<u8>> =
+        serde_json::from_str(&serde_json::to_string(&sam).unwrap()).unwrap();
+    assert!(loaded.get_root_state().feed_bytes("bcab").is_accepting());
+    assert!(!loaded.get_root_state().feed_bytes("bca").is_accepting());
+}
+
+fn corrupt<TransTable: TransitionTable, F: FnOnce(&mut Value)>(
+    sam: &GeneralSam<TransTable>,
+    f: F,
+) -> bool
+where
+    GeneralSam<TransTable>: Serialize + DeserializeOwned,
+{
+    let mut value = serde_json::to_value(sam).unwrap();
+    f(&mut value);
+    serde_json::from_value::<GeneralSam<TransTable>>(value).is_err()
+}
+
+#[test]
+fn test_serde_validation() {
+    let sam = build_sam();
+    let num_of_nodes = sam.num_of_nodes();
+    let root_trans = |v: &mut Value| v["node_pool"][1]["trans"].clone();
+
+    assert!(!corrupt(&sam, |_| {}));
+    assert!(corrupt(&sam, |v| v["node_pool"][1]["trans"]["97"] =
+        json!(num_of_nodes)));
+    assert!(corrupt(&sam, |v| v["node_pool"][2]["trans"]["97"] = json!(1)));
+    assert!(corrupt(&sam, |v| v["node_pool"][0]["trans"] = root_trans(v)));
+    assert!(corrupt(&sam, |v| v["node_pool"][0]["len"] = json!(u64::MAX)));
+    assert!(corrupt(&sam, |v| v["node_pool"][0]["link"] = json!(1)));
+    assert!(corrupt(&sam, |v| v["node_pool"][2]["link"] = json!(num_of_nodes)));
+    assert!(corrupt(&sam, |v| v["node_pool"][2]["link"] = json!(2)));
+    assert!(corrupt(&sam, |v| v["first_occurrences"][2] = json!([0, 0])));
+    assert!(corrupt(&sam, |v| v["prefix_seq_ids"]["2"] = json!([3])));
+    assert!(corrupt(&sam, |v| v["seq_node_starts"][0] = json!(num_of_nodes + 1)));
+    assert!(corrupt(&sam, |v| v["end_counts"]["0"] = json!(1)));
+    assert!(corrupt(&sam, |v| v["topo_and_suf_len_sorted_order"][0] = json!(0)));
+    assert!(corrupt(&sam, |v| v["occurrence_counts"] = json!([1, 2])));
+    assert!(corrupt(&sam, |v| v["node_pool"] = json!([])));
+
+    // a transition to a shorter node would make a cycle
+    let cyclic = |v: &mut Value| {
+        let last = v["node_pool"].as_array().unwrap().len() - 1;
+        v["node_pool"][last]["trans"]["97"] = json!(2);
+    };
+    assert!(corrupt(&sam, cyclic));
+
+    let bisect: GeneralSam<VecBisectTable<u8>> = sam.alter_trans_table();
+    assert!(!corrupt(&bisect, |_| {}));
+    assert!(corrupt(&bisect, |v| {
+        v["node_pool"][1]["trans"].as_array_mut().unwrap().reverse()
+    }));
+
+    let whole: GeneralSam<WholeAlphabetTable<u8, Vec<Option<GeneralSamNodeID>>>> =
+        sam.alter_trans_table();
+    assert!(!corrupt(&whole, |_| {}));
+    assert!(corrupt(&whole, |v| {
+        v["node_pool"][1]["trans"].as_array_mut().unwrap().pop();
+    }));
+}
+
+#[cfg(feature = "trie")]
+#[test]
+fn test_serde_trie() {
+    use crate::Trie;
+
+    let mut trie = Trie::<BTreeTransTable<char>>::default();
+    trie.insert("hello".chars());
+    trie.insert("Chielo".chars());
+    trie.insert("help".chars());
+
+    let json = serde_json::to_string(&trie).unwrap();
+    let loaded: Trie<BTreeTransTable<char>> = serde_json::from_str(&json).unwrap();
+    assert_isomorphic(
+        &GeneralSam::<BTreeTransTable<char>>::from_trie(trie.get_root_state()),
+        &GeneralSam::<BTreeTransTable<char>>::from_trie(loaded.get_root_state()),
+    );
+
+    let mut value = serde_json::to_value(&trie).unwrap();
+    value["node_pool"][1]["trans"]["h"] = json!(trie.num_of_nodes());
+    assert!(serde_json::from_value::<Trie<BTreeTransTable<char>>>(value).is_err());
+
+    let mut value = serde_json::to_value(&trie).unwrap();
+    value["node_pool"][2]["parent"] = json!(3);
+    assert!(serde_json::from_value::<Trie<BTreeTransTable<char>>>(value).is_err());
+}
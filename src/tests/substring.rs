@@ -0,0 +1,401 @@
+use std::collections::BTreeSet;
+
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use super::{build_sam, random_strings};
+use crate::{
+    BTreeTransTable, GeneralSam, HashTransTable, RepeatClass, SubstringRank, SuffixLinkTree,
+};
+
+pub(super) fn all_substrings(strings: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    let mut res = Vec::new();
+    for s in strings.iter() {
+        for i in 0..s.len() {
+            for j in i + 1..=s.len() {
+                res.push(s[i..j].to_vec());
+            }
+        }
+    }
+    res.sort();
+    res
+}
+
+#[test]
+fn test_kth_substring_and_rank() {
+    let mut rng = StdRng::seed_from_u64(7123948712394);
+    for _ in 0..200 {
+        let strings = random_strings(&mut rng, 3, 16, 3);
+        let sam = build_sam::<BTreeTransTable<u8>>(&strings);
+
+        let distinct: Vec<_> = all_substrings(&strings)
+            .into_iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let rank = SubstringRank::build(&sam);
+        assert_eq!(rank.num_of_substrings(), distinct.len());
+        for (k, s) in distinct.iter().enumerate() {
+            assert_eq!(rank.kth_substring(&sam, k).as_ref(), Some(s));
+            assert_eq!(rank.rank(&sam, s.iter().copied()), Some(k));
+        }
+        assert_eq!(rank.kth_substring(&sam, distinct.len()), None);
+        assert_eq!(rank.rank(&sam, b"d".iter().copied()), None);
+        assert_eq!(rank.rank(&sam, None), None);
+
+        // strings shared by several sequences are counted in every sequence
+        let with_multiplicity = all_substrings(&strings);
+        let rank = SubstringRank::build_with_multiplicity(&sam).unwrap();
+        assert_eq!(rank.num_of_substrings(), with_multiplicity.len());
+        for (k, s) in with_multiplicity.iter().enumerate() {
+            assert_eq!(rank.kth_substring(&sam, k).as_ref(), Some(s));
+            let first = with_multiplicity.iter().position(|x| x == s).unwrap();
+            assert_eq!(rank.rank(&sam, s.iter().copied()), Some(first));
+        }
+    }
+
+    let sam = GeneralSam::<HashTransTable<char>>::from_chars("banana");
+    let rank = SubstringRank::build(&sam);
+    assert!(SubstringRank::build_with_multiplicity(&sam).is_none());
+    let kth = |k| rank.kth_substring(&sam, k).map(String::from_iter);
+    assert_eq!(kth(0).as_deref(), Some("a"));
+    assert_eq!(kth(1).as_deref(), Some("an"));
+    assert_eq!(kth(5).as_deref(), Some("b"));
+    assert_eq!(rank.rank(&sam, "nana".chars()), Some(14));
+}
+
+#[test]
+fn test_substring_iter() {
+    let mut rng = StdRng::seed_from_u64(9182734981723);
+    for _ in 0..200 {
+        let strings = random_strings(&mut rng, 3, 16, 3);
+        let sam = build_sam::<BTreeTransTable<u8>>(&strings);
+        let all = all_substrings(&strings);
+        let distinct: BTreeSet<_> = all.iter().cloned().collect();
+
+        let output: Vec<_> = sam.substrings(..).collect();
+        assert_eq!(
+            output.iter().map(|x| x.keys.clone()).collect::<Vec<_>>(),
+            distinct.iter().cloned().collect::<Vec<_>>()
+        );
+        for entry in output.iter() {
+            let mut state = sam.get_root_state();
+            state.feed_bytes(&entry.keys);
+            assert_eq!(state.node_id, entry.node_id);
+            assert_eq!(entry.occurrence_count, state.occurrence_count());
+        }
+
+        let (lo, hi) = (rng.random_range(0..5), rng.random_range(0..8));
+        let prefix: Vec<u8> = (0..rng.random_range(0..3))
+            .map(|_| b'a' + rng.random_range(0..3))
+            .collect();
+        let expected: Vec<_> = distinct
+            .iter()
+            .filter(|x| x.starts_with(&prefix) && lo <= x.len() && x.len() < hi)
+            .cloned()
+            .collect();
+        let output: Vec<_> = sam
+            .substrings_with_prefix(prefix.iter().copied(), lo..hi)
+            .map(|x| x.keys)
+            .collect();
+        assert_eq!(output, expected);
+
+        let expected: Vec<_> = distinct.iter().filter(|x| x.len() == 2).cloned().collect();
+        let output: Vec<_> = sam.substrings(2..=2).map(|x| x.keys).collect();
+        assert_eq!(output, expected);
+    }
+}
+
+pub(super) fn prefix_positions(strings: &[Vec<u8>]) -> Vec<(Vec<u8>, usize)> {
+    let mut res = Vec::new();
+    for (seq_id, s) in strings.iter().enumerate() {
+        for i in 1..=s.len() {
+            res.push((s[..i].to_vec(), seq_id));
+        }
+    }
+    res
+}
+
+pub(super) fn occurrences_of(prefixes: &[(Vec<u8>, usize)], pattern: &[u8]) -> Vec<(usize, usize)> {
+    let mut res: Vec<_> = prefixes
+        .iter()
+        .filter(|(p, _)| p.ends_with(pattern))
+        .map(|(p, seq_id)| (*seq_id, p.len() - pattern.len()))
+        .collect();
+    res.sort();
+    res
+}
+
+#[test]
+fn test_repeats() {
+    let mut rng = StdRng::seed_from_u64(4519238471923);
+    for _ in 0..200 {
+        let strings = random_strings(&mut rng, 3, 20, 3);
+        let sam = build_sam::<BTreeTransTable<u8>>(&strings);
+        let tree = SuffixLinkTree::build(&sam);
+        let prefixes = prefix_positions(&strings);
+        let distinct: BTreeSet<_> = all_substrings(&strings).into_iter().collect();
+
+        for min_occurrences in 0..4 {
+            let expected_len = distinct
+                .iter()
+                .filter(|x| occurrences_of(&prefixes, x).len() >= min_occurrences)
+                .map(|x| x.len())
+                .max();
+            let output = sam.longest_repeated_substring(&tree, min_occurrences);
+            assert_eq!(output.as_ref().map(|x| x.len), expected_len);
+            if let Some(output) = output {
+                let mut state = sam.get_root_state();
+                let (seq_id, start) = output.positions[0];
+                state.feed_bytes(&strings[seq_id][start..start + output.len]);
+                assert_eq!(state.node_id, output.node_id);
+                let expected =
+                    occurrences_of(&prefixes, &strings[seq_id][start..start + output.len]);
+                assert_eq!(output.positions, expected);
+            }
+
+            let min_len = rng.random_range(0..4);
+            let expected: BTreeSet<_> = distinct
+                .iter()
+                .filter(|x| {
+                    x.len() >= min_len && occurrences_of(&prefixes, x).len() >= min_occurrences
+                })
+                .cloned()
+                .collect();
+            let mut output = BTreeSet::new();
+            for repeat in sam.repeats(min_len, min_occurrences) {
+                assert!(repeat.occurrence_count >= min_occurrences);
+                for entry in sam.substrings(repeat.min_len..=repeat.max_len) {
+                    if entry.node_id == repeat.node_id {
+                        assert!(output.insert(entry.keys));
+                    }
+                }
+            }
+            assert_eq!(output, expected);
+        }
+    }
+}
+
+#[test]
+fn test_maximal_repeats() {
+    let mut rng = StdRng::seed_from_u64(6238741982374);
+    for _ in 0..200 {
+        let strings = random_strings(&mut rng, 3, 20, 3);
+        let sam = build_sam::<BTreeTransTable<u8>>(&strings);
+        let tree = SuffixLinkTree::build(&sam);
+        let prefixes = prefix_positions(&strings);
+        let distinct: BTreeSet<_> = all_substrings(&strings).into_iter().collect();
+
+        // ends and starts of sequences are distinguished by the sequence ids,
+        // even if the sequences are identical
+        let right_contexts = |p: &[u8]| -> BTreeSet<Result<u8, usize>> {
+            let mut res = BTreeSet::new();
+            for (seq_id, s) in strings.iter().enumerate() {
+                if s.ends_with(p) {
+                    res.insert(Err(seq_id));
+                }
+                for w in s.windows(p.len() + 1) {
+                    if &w[..p.len()] == p {
+                        res.insert(Ok(w[p.len()]));
+                    }
+                }
+            }
+            res
+        };
+        let left_contexts = |p: &[u8]| -> BTreeSet<Result<u8, usize>> {
+            let mut res = BTreeSet::new();
+            for (seq_id, s) in strings.iter().enumerate() {
+                if s.starts_with(p) {
+                    res.insert(Err(seq_id));
+                }
+                for w in s.windows(p.len() + 1) {
+                    if &w[1..] == p {
+                        res.insert(Ok(w[0]));
+                    }
+                }
+            }
+            res
+        };
+        let is_repeat = |p: &[u8]| occurrences_of(&prefixes, p).len() >= 2;
+
+        let collect = |repeats: Vec<RepeatClass>| -> BTreeSet<Vec<u8>> {
+            let mut res = BTreeSet::new();
+            for repeat in repeats {
+                for entry in sam.substrings(repeat.min_len..=repeat.max_len) {
+                    if entry.node_id == repeat.node_id {
+                        assert!(res.insert(entry.keys));
+                    }
+                }
+            }
+            res
+        };
+
+        let right_maximal: BTreeSet<_> = distinct
+            .iter()
+            .filter(|p| is_repeat(p) && right_contexts(p).len() >= 2)
+            .cloned()
+            .collect();
+        assert_eq!(
+            collect(sam.right_maximal_repeats().collect()),
+            right_maximal
+        );
+
+        let left_maximal: BTreeSet<_> = distinct
+            .iter()
+            .filter(|p| is_repeat(p) && left_contexts(p).len() >= 2)
+            .cloned()
+            .collect();
+        assert_eq!(
+            collect(sam.left_maximal_repeats(&tree).collect()),
+            left_maximal
+        );
+
+        let maximal: BTreeSet<_> = left_maximal.intersection(&right_maximal).cloned().collect();
+        assert_eq!(collect(sam.maximal_repeats(&tree).collect()), maximal);
+
+        let supermaximal: BTreeSet<_> = maximal
+            .iter()
+            .filter(|p| {
+                !maximal
+                    .iter()
+                    .any(|q| q.len() > p.len() && q.windows(p.len()).any(|w| w == &p[..]))
+            })
+            .cloned()
+            .collect();
+        assert_eq!(
+            collect(sam.supermaximal_repeats(&tree).collect()),
+            supermaximal
+        );
+    }
+}
+
+#[test]
+fn test_unique_and_absent_words() {
+    let mut rng = StdRng::seed_from_u64(8812734019283);
+    for _ in 0..200 {
+        let strings = random_strings(&mut rng, 3, 16, 3);
+        let sam = build_sam::<BTreeTransTable<u8>>(&strings);
+        let prefixes = prefix_positions(&strings);
+        let count = |s: &[u8]| {
+            if s.is_empty() {
+                usize::MAX
+            } else {
+                occurrences_of(&prefixes, s).len()
+            }
+        };
+
+        let distinct: BTreeSet<_> = all_substrings(&strings).into_iter().collect();
+        let mut unique: Vec<_> = distinct
+            .iter()
+            .filter(|s| count(s) == 1 && count(&s[1..]) >= 2 && count(&s[..s.len() - 1]) >= 2)
+            .map(|s| (s.clone(), occurrences_of(&prefixes, s)[0]))
+            .collect();
+        unique.sort_by(|a, b| (a.0.len(), &a.0).cmp(&(b.0.len(), &b.0)));
+        let output: Vec<_> = sam
+            .minimal_unique_substrings()
+            .into_iter()
+            .map(|x| {
+                assert_eq!(sam.get_state(x.node_id).occurrence_count(), Some(1));
+                (x.keys, x.position)
+            })
+            .collect();
+        assert_eq!(output, unique);
+
+        let shortest: Vec<_> = unique
+            .iter()
+            .filter(|x| x.0.len() == unique[0].0.len())
+            .cloned()
+            .collect();
+        let output: Vec<_> = sam
+            .shortest_unique_substrings()
+            .into_iter()
+            .map(|x| (x.keys, x.position))
+            .collect();
+        assert_eq!(output, shortest);
+
+        let is_substring = |s: &[u8]| s.is_empty() || distinct.contains(s);
+        let mut absent: Vec<_> = distinct
+            .iter()
+            .map(Vec::as_slice)
+            .chain([&[][..]])
+            .flat_map(|s| b"abcd".iter().map(move |c| [&[*c][..], s].concat()))
+            .filter(|w| !is_substring(w) && is_substring(&w[..w.len() - 1]))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        absent.sort_by(|a, b| (a.len(), a).cmp(&(b.len(), b)));
+        assert_eq!(sam.minimal_absent_words(b"dcbaa".iter().copied()), absent);
+
+        absent.retain(|w| w.iter().all(|c| b"ab".contains(c)));
+        assert_eq!(sam.minimal_absent_words(*b"ab"), absent);
+    }
+}
+
+#[test]
+fn test_absent_words_outside_alphabet() {
+    let sam = GeneralSam::<BTreeTransTable<u8>>::from_bytes("axb");
+    let absent: Vec<_> = ["aa", "ba", "bb", "ab"]
+        .map(|s| s.as_bytes().to_vec())
+        .into_iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    assert_eq!(sam.minimal_absent_words(*b"ab"), absent);
+}
+
+#[test]
+fn test_unique_substrings_after_insertion() {
+    let mut sam = build_sam::<BTreeTransTable<u8>>(&[b"abab".to_vec()]);
+    sam.insert_bytes("cabd");
+    // the occurrence counts are stale until computed again
+    sam.minimal_unique_substrings();
+    sam.compute_occurrence_counts();
+    let output: Vec<_> = sam
+        .minimal_unique_substrings()
+        .into_iter()
+        .map(|x| x.keys)
+        .collect();
+    assert_eq!(output, [&b"c"[..], b"d", b"ba"]);
+}
+
+#[test]
+fn test_shortest_absent_string() {
+    let mut rng = StdRng::seed_from_u64(1029384756102);
+    for _ in 0..200 {
+        let strings = random_strings(&mut rng, 3, 24, 3);
+        let sam = build_sam::<BTreeTransTable<u8>>(&strings);
+        let distinct: BTreeSet<_> = all_substrings(&strings).into_iter().collect();
+
+        let mut layer = vec![Vec::new()];
+        let mut expected = None;
+        for len in 0..6 {
+            let absent = layer
+                .iter()
+                .filter(|s| !s.is_empty() && !distinct.contains(*s));
+            assert_eq!(
+                sam.count_absent_strings(b"cab".iter().copied(), len),
+                Some(absent.clone().count())
+            );
+            if expected.is_none() {
+                expected = absent.min().cloned();
+            }
+            layer = layer
+                .iter()
+                .flat_map(|s| b"abc".iter().map(move |c| [&s[..], &[*c]].concat()))
+                .collect();
+        }
+        assert_eq!(
+            sam.shortest_absent_string(b"cbac".iter().copied()),
+            expected
+        );
+    }
+
+    let sam = build_sam::<BTreeTransTable<u8>>(&[b"ab".to_vec()]);
+    assert_eq!(sam.shortest_absent_string(None), None);
+    assert_eq!(
+        sam.shortest_absent_string(b"ba".iter().copied()),
+        Some(b"aa".to_vec())
+    );
+    assert_eq!(sam.count_absent_strings(b"ab".iter().copied(), 0), Some(0));
+    assert_eq!(sam.count_absent_strings(b"ab".iter().copied(), 64), None);
+}
@@ -2,7 +2,11 @@ use rand::distr::{Alphanumeric, SampleString};
 use rand::rngs::StdRng;
 use rand::{RngExt, SeedableRng};
 
-use crate::{BTreeTransTable, GeneralSam, SAM_ROOT_NODE_ID, Trie};
+use super::online::assert_isomorphic;
+use crate::{
+    BTreeTransTable, DocumentIndex, GeneralSam, HashTransTable, NodeIndex, SAM_ROOT_NODE_ID,
+    TransitionTable, Trie, TrieNodeAlike,
+};
 
 #[test]
 fn test_example_from_trie() {
@@ -115,3 +119,235 @@ fn test_topo_and_suf_len_sorted_order() {
         });
     }
 }
+
+#[test]
+fn test_insert_same_as_from_trie() {
+    let mut rng = StdRng::seed_from_u64(9823749812734);
+    for _ in 0..2000 {
+        let mut trie = Trie::<BTreeTransTable<u8>>::default();
+        let mut sam = GeneralSam::<BTreeTransTable<u8>>::default();
+        for _ in 0..rng.random_range(0..16) {
+            let len = rng.random_range(0..12);
+            let alphabet = rng.random_range(1..4);
+            let string: Vec<u8> = (0..len)
+                .map(|_| b'a' + rng.random_range(0..alphabet))
+                .collect();
+            trie.insert_bytes(&string);
+            sam.insert_bytes(&string);
+
+            if rng.random_bool(0.25) {
+                sam.refresh();
+                let expected = GeneralSam::from_trie(trie.get_root_state());
+                assert_isomorphic(&sam, &expected);
+            }
+        }
+        sam.refresh();
+        let expected = GeneralSam::from_trie(trie.get_root_state());
+        assert_isomorphic(&sam, &expected);
+    }
+}
+
+#[test]
+fn test_occurrence_count_with_trie() {
+    let mut rng = StdRng::seed_from_u64(1298374918723);
+    for _ in 0..200 {
+        let mut trie = Trie::<BTreeTransTable<u8>>::default();
+        let mut words = Vec::new();
+        for _ in 0..rng.random_range(0..8) {
+            let len = rng.random_range(0..10);
+            let word: Vec<u8> = (0..len).map(|_| b'a' + rng.random_range(0..3)).collect();
+            trie.insert_bytes(&word);
+            words.push(word);
+        }
+
+        let mut sam = GeneralSam::<BTreeTransTable<u8>>::from_trie(trie.get_root_state());
+        sam.compute_occurrence_counts();
+
+        let prefixes: std::collections::BTreeSet<&[u8]> = words
+            .iter()
+            .flat_map(|w| (1..=w.len()).map(move |i| &w[..i]))
+            .collect();
+        for word in words.iter() {
+            for i in 0..word.len() {
+                for j in i + 1..=word.len() {
+                    let pattern = &word[i..j];
+                    let expected = prefixes.iter().filter(|p| p.ends_with(pattern)).count();
+                    let mut state = sam.get_root_state();
+                    state.feed_bytes(pattern);
+                    assert_eq!(state.occurrence_count(), Some(expected));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_document_index() {
+    let mut rng = StdRng::seed_from_u64(3491827349182);
+    for _ in 0..200 {
+        let mut trie = Trie::<BTreeTransTable<u8>>::default();
+        let mut words = Vec::new();
+        for _ in 0..rng.random_range(0..8) {
+            let len = rng.random_range(0..10);
+            let word: Vec<u8> = (0..len).map(|_| b'a' + rng.random_range(0..3)).collect();
+            trie.insert_bytes(&word);
+            words.push(word);
+        }
+
+        let sam = GeneralSam::<BTreeTransTable<u8>>::from_trie(trie.get_root_state());
+        let index = DocumentIndex::build(&sam, trie.get_root_state(), |tn| tn.node_id);
+
+        let contains = |doc: &[u8], pattern: &[u8]| {
+            pattern.is_empty() || doc.windows(pattern.len()).any(|w| w == pattern)
+        };
+        for word in words.iter() {
+            for i in 0..word.len() {
+                for j in i..=word.len() {
+                    let pattern = &word[i..j];
+                    let mut expected: Vec<_> = words
+                        .iter()
+                        .filter(|doc| contains(doc, pattern))
+                        .map(|doc| {
+                            let mut state = trie.get_root_state();
+                            state.feed_slice(doc);
+                            state.node_id
+                        })
+                        .collect();
+                    expected.sort();
+                    expected.dedup();
+
+                    let mut state = sam.get_root_state();
+                    state.feed_bytes(pattern);
+                    assert_eq!(state.documents(&index), expected);
+                    assert_eq!(state.document_frequency(&index), expected.len());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_trie_narrow_node_ids() {
+    let mut trie = Trie::<BTreeTransTable<char, u16>>::default();
+    for word in ["hello", "Chielo", "help"] {
+        trie.insert_chars(word);
+    }
+    let wide: Trie<BTreeTransTable<char>> = trie.alter_trans_table();
+    for node_id in 0..trie.num_of_nodes() {
+        let (lhs, rhs) = (
+            trie.get_node(node_id).unwrap(),
+            wide.get_node(node_id).unwrap(),
+        );
+        assert_eq!(lhs.get_parent(), rhs.get_parent());
+        assert!(
+            lhs.get_trans()
+                .transitions()
+                .map(|x| x.index())
+                .eq(rhs.get_trans().transitions().copied())
+        );
+    }
+    assert_isomorphic(
+        &GeneralSam::<BTreeTransTable<char>>::from_trie(trie.get_root_state()),
+        &GeneralSam::from_trie(wide.get_root_state()),
+    );
+
+    let mut trie = Trie::<BTreeTransTable<u8>>::default();
+    trie.insert(std::iter::repeat_n(b'a', u16::MAX as usize + 1));
+    assert!(
+        trie.try_alter_trans_table::<BTreeTransTable<u8, u16>>()
+            .is_err()
+    );
+    let mut trie = trie
+        .try_alter_trans_table::<BTreeTransTable<u8, u32>>()
+        .unwrap();
+    trie.insert_bytes("b");
+    assert!(trie.get_root_state().next_states().count() == 2);
+}
+
+#[test]
+fn test_trie_double_array() {
+    let mut trie = Trie::<BTreeTransTable<char>>::default();
+    for word in ["hello", "Chielo", "help", "你好", "🧡"] {
+        trie.insert_chars(word);
+    }
+    let double_array = trie.to_double_array();
+    for node_id in 0..trie.num_of_nodes() {
+        let (lhs, rhs) = (
+            trie.get_node(node_id).unwrap(),
+            double_array.get_node(node_id).unwrap(),
+        );
+        assert_eq!(lhs.get_parent(), rhs.get_parent());
+        assert_eq!(lhs.accept, rhs.accept);
+        assert!(TransitionTable::iter(lhs.get_trans()).eq(rhs.get_trans().iter()));
+    }
+    assert_isomorphic(
+        &GeneralSam::<BTreeTransTable<char>>::from_trie(trie.get_root_state()),
+        &GeneralSam::from_trie(double_array.get_root_state()),
+    );
+
+    let mut state = double_array.get_root_state();
+    state.feed("hel".chars());
+    assert!(!state.is_nil() && !state.is_accepting());
+    state.feed("p".chars());
+    assert!(state.is_accepting());
+    state.feed("!".chars());
+    assert!(state.is_nil());
+}
+
+#[test]
+fn test_trie_freeze() {
+    let mut trie = Trie::<HashTransTable<char>>::default();
+    for word in ["hello", "Chielo", "help", "你好", "🧡"] {
+        trie.insert_chars(word);
+    }
+    let sorted: Trie<BTreeTransTable<char>> = trie.alter_trans_table();
+    let frozen = trie.freeze();
+    for node_id in 0..trie.num_of_nodes() {
+        let (lhs, rhs) = (
+            sorted.get_node(node_id).unwrap(),
+            frozen.get_node(node_id).unwrap(),
+        );
+        assert_eq!(lhs.get_parent(), rhs.get_parent());
+        assert_eq!(lhs.accept, rhs.accept);
+        assert!(TransitionTable::iter(lhs.get_trans()).eq(rhs.get_trans().iter()));
+    }
+    assert_isomorphic(
+        &GeneralSam::<BTreeTransTable<char>>::from_trie(sorted.get_root_state()),
+        &GeneralSam::from_trie(frozen.get_root_state()),
+    );
+}
+
+#[test]
+fn test_trie_node_id_overflow() {
+    let mut rng = StdRng::seed_from_u64(20240101);
+    let string = Alphanumeric.sample_string(&mut rng, 1 << 16);
+    let mut trie = Trie::<BTreeTransTable<u8, u16>>::default();
+    let err = trie.try_insert(string.bytes()).unwrap_err();
+    assert_eq!(err.max_node_id, u16::MAX as usize);
+    assert_eq!(trie.num_of_nodes(), u16::MAX as usize + 1);
+}
+
+#[test]
+fn test_first_occurrence_with_trie() {
+    // a trie is one sequence whose end positions are depths
+    let mut trie = Trie::<BTreeTransTable<char>>::default();
+    trie.insert_chars("xyz");
+    trie.insert_chars("abc");
+    let mut sam = GeneralSam::<BTreeTransTable<char>>::from_trie(trie.get_root_state());
+    sam.compute_first_occurrences();
+    assert_eq!(
+        sam.get_root_state().feed_chars("bc").first_occurrence(2),
+        Some((0, 1))
+    );
+
+    // the inserted strings are told apart
+    let mut sam = GeneralSam::<BTreeTransTable<char>>::default();
+    sam.insert_chars("xyz");
+    sam.insert_chars("abc");
+    sam.refresh();
+    sam.compute_first_occurrences();
+    assert_eq!(
+        sam.get_root_state().feed_chars("bc").first_occurrence(2),
+        Some((1, 1))
+    );
+}
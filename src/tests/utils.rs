@@ -84,7 +84,7 @@ mod trie {
     use crate::utils::rope::RopeBase;
     use crate::utils::suffixwise::{SuffixInTrie, SuffixInTrieData};
     use crate::utils::tokenize::GreedyTokenizer;
-    use crate::{BTreeTransTable, GeneralSam, TransitionTable, Trie};
+    use crate::{BTreeTransTable, DoubleArrayKey, GeneralSam, TransitionTable, Trie};
 
     #[test]
     fn test_suffix_in_trie_data() {
@@ -269,6 +269,31 @@ mod trie {
         tokenizer_cases::<_, HashTransTable<_>, _>(vocab_size, &mut f);
         tokenizer_cases::<_, VecBisectTable<_>, _>(vocab_size, &mut f);
         tokenizer_cases::<_, BoxBisectTable<_>, _>(vocab_size, &mut f);
+        tokenizer_cases::<_, BTreeTransTable<_, u32>, _>(vocab_size, &mut f);
+        tokenizer_cases::<_, VecBisectTable<_, u32>, _>(vocab_size, &mut f);
+    }
+
+    fn tokenizer_cases_with_double_array<
+        T: Clone + Ord + Eq + std::hash::Hash + DoubleArrayKey,
+        F: FnMut(String) -> Vec<T>,
+    >(
+        vocab_size: usize,
+        f: &mut F,
+    ) {
+        let mut rng = StdRng::seed_from_u64(2837461928374);
+        let mut trie = Trie::<BTreeTransTable<T>>::default();
+        for _ in 0..vocab_size {
+            let len = rng.random_range(0..8);
+            trie.insert(f(Alphanumeric.sample_string(&mut rng, len)));
+        }
+        let sam = GeneralSam::<BTreeTransTable<T>>::from_trie(trie.get_root_state());
+        let (sam, trie) = (sam.to_double_array(), trie.to_double_array());
+        let tokenizer = GreedyTokenizer::build_from_trie(&sam, trie.get_root_state());
+        for _ in 0..32 {
+            let len = rng.random_range(0..4096);
+            let string = Alphanumeric.sample_string(&mut rng, len);
+            case_tokenizer(&tokenizer, &trie, f(string));
+        }
     }
 
     #[test]
@@ -278,6 +303,7 @@ mod trie {
             tokenizer_cases_with_all_backends::<u8, _>(i, &mut f);
             tokenizer_cases::<_, WholeAlphabetTable<_, Vec<_>>, _>(i, &mut f);
             tokenizer_cases::<_, WholeAlphabetTable<_, Box<[_]>>, _>(i, &mut f);
+            tokenizer_cases_with_double_array::<u8, _>(i, &mut f);
         }
     }
 
@@ -286,6 +312,7 @@ mod trie {
         for i in [10, 16] {
             let mut f = |s: String| s.chars().collect();
             tokenizer_cases_with_all_backends::<char, _>(i, &mut f);
+            tokenizer_cases_with_double_array::<char, _>(i, &mut f);
         }
     }
 
@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use crate::{BTreeTransTable, SlidingWindowSam};
+
+#[test]
+fn test_sliding_window_sam() {
+    let mut rng = StdRng::seed_from_u64(7781236491823);
+    for _ in 0..100 {
+        let capacity = rng.random_range(0..10);
+        let mut sam = SlidingWindowSam::<BTreeTransTable<u8>>::new(capacity);
+        let mut window = VecDeque::new();
+        for _ in 0..100 {
+            if rng.random_range(0..4) == 0 {
+                assert_eq!(sam.pop_front(), window.pop_front());
+            } else {
+                let key = b'a' + rng.random_range(0..3);
+                window.push_back(key);
+                let expired = if window.len() > capacity {
+                    window.pop_front()
+                } else {
+                    None
+                };
+                assert_eq!(sam.push_back(key), expired);
+            }
+            assert_eq!(sam.len(), window.len());
+            assert!(sam.iter().eq(window.iter()));
+            assert!(sam.num_of_nodes() <= 4 * capacity + 2);
+
+            let window: Vec<_> = window.iter().copied().collect();
+            for _ in 0..10 {
+                let len = rng.random_range(0..5);
+                let pattern: Vec<u8> = (0..len).map(|_| b'a' + rng.random_range(0..3)).collect();
+                let occurs = len == 0 || window.windows(len).any(|w| w == pattern);
+                assert_eq!(sam.contains(pattern.iter().copied()), occurs);
+                assert_eq!(
+                    sam.ends_with(pattern.iter().copied()),
+                    window.ends_with(&pattern)
+                );
+
+                // the state stays non-nil along the prefixes in the window
+                let mut state = sam.get_root_state();
+                for (i, key) in pattern.iter().enumerate() {
+                    state.goto(key);
+                    let prefix = &pattern[..=i];
+                    let occurs = window.windows(i + 1).any(|w| w == prefix);
+                    assert_eq!(!state.is_nil(), occurs);
+                    assert_eq!(state.is_accepting(), window.ends_with(prefix));
+                }
+            }
+        }
+    }
+
+    // a run of a single key, whose suffix link chain spans the whole window
+    let capacity = 1 << 14;
+    let mut sam = SlidingWindowSam::<BTreeTransTable<u8>>::new(capacity);
+    for _ in 0..8 * capacity {
+        sam.push_back(b'a');
+    }
+    sam.push_back(b'b');
+    assert!(sam.contains(vec![b'a'; capacity - 1]));
+    assert!(!sam.contains(vec![b'a'; capacity]));
+    assert!(sam.ends_with([b'a', b'b']));
+
+    // a periodic stream, whose suffixes alternate between two long chains
+    let mut sam = SlidingWindowSam::<BTreeTransTable<u8>>::new(capacity);
+    for i in 0..8 * capacity {
+        sam.push_back(b"ab"[i % 2]);
+    }
+    let mut state = sam.get_root_state();
+    state.feed_bytes(b"ab".repeat(capacity / 2));
+    assert!(state.is_accepting());
+    assert!(state.feed_bytes("a").is_nil());
+    let mut state = sam.get_root_state();
+    state.feed_bytes(b"ba".repeat(capacity / 2 - 1));
+    assert!(!state.is_nil() && !state.is_accepting());
+    assert!(state.feed_bytes("b").is_accepting());
+}
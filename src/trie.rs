@@ -3,20 +3,46 @@
 use std::borrow::Borrow;
 use std::ops::Deref;
 
-use crate::{ConstructiveTransitionTable, GeneralSamNodeID, TransitionTable, TrieNodeAlike};
+use crate::table::convert_trans_table;
+use crate::{
+    ConstructiveTransitionTable, DoubleArrayKey, DoubleArrayTable, FlatTransTable,
+    GeneralSamNodeID, NodeIdOverflow, NodeIndex, TransitionTable, TrieNodeAlike,
+};
 
 pub type TrieNodeID = GeneralSamNodeID;
 pub const TRIE_NIL_NODE_ID: TrieNodeID = 0;
 pub const TRIE_ROOT_NODE_ID: TrieNodeID = 1;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "TransTable: serde::Serialize, TransTable::NodeID: serde::Serialize",
+        deserialize = "TransTable: serde::Deserialize<'de>, \
+                       TransTable::NodeID: serde::Deserialize<'de>"
+    ))
+)]
 pub struct TrieNode<TransTable: TransitionTable> {
     trans: TransTable,
-    parent: TrieNodeID,
+    parent: TransTable::NodeID,
     pub accept: bool,
 }
 
+/// A trie.
+///
+/// Like [`crate::GeneralSam`], node ids are stored as [`TransitionTable::NodeID`],
+/// and the insertion panics once the ids overflow it,
+/// unless it is done by [`Trie::try_insert`].
+///
+/// With the `serde` feature, it can be serialized,
+/// and the node ids are validated on deserialization.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(bound = "TransTable: serde::Serialize, TransTable::NodeID: serde::Serialize")
+)]
 pub struct Trie<TransTable: TransitionTable> {
     node_pool: Vec<TrieNode<TransTable>>,
 }
@@ -42,7 +68,7 @@ impl<TransTable: ConstructiveTransitionTable> TrieNode<TransTable> {
     fn new(parent: TrieNodeID) -> Self {
         Self {
             trans: Default::default(),
-            parent,
+            parent: TransTable::NodeID::from_index(parent),
             accept: Default::default(),
         }
     }
@@ -54,15 +80,15 @@ impl<TransTable: TransitionTable> TrieNode<TransTable> {
     }
 
     pub fn get_parent(&self) -> TrieNodeID {
-        self.parent
+        self.parent.index()
     }
 
     fn alter_trans_table<NewTableType: TransitionTable<KeyType = TransTable::KeyType>>(
         &self,
     ) -> TrieNode<NewTableType> {
         TrieNode {
-            trans: NewTableType::from_kv_iter(self.trans.iter()),
-            parent: self.parent,
+            trans: convert_trans_table(&self.trans),
+            parent: NewTableType::NodeID::from_index(self.get_parent()),
             accept: self.accept,
         }
     }
@@ -109,6 +135,10 @@ impl<TransTable: TransitionTable> Trie<TransTable> {
         self.get_state(TRIE_ROOT_NODE_ID)
     }
 
+    /// # Panics
+    ///
+    /// Panics if the node ids overflow [`TransitionTable::NodeID`] of the new table,
+    /// see [`Trie::try_alter_trans_table`].
     pub fn alter_trans_table<NewTableType: TransitionTable<KeyType = TransTable::KeyType>>(
         &self,
     ) -> Trie<NewTableType> {
@@ -120,32 +150,109 @@ impl<TransTable: TransitionTable> Trie<TransTable> {
                 .collect(),
         }
     }
+
+    /// Like [`Trie::alter_trans_table`],
+    /// but fails if the node ids overflow the new node id type.
+    pub fn try_alter_trans_table<NewTableType: TransitionTable<KeyType = TransTable::KeyType>>(
+        &self,
+    ) -> Result<Trie<NewTableType>, NodeIdOverflow> {
+        NodeIdOverflow::check::<NewTableType::NodeID>(self.num_of_nodes())?;
+        Ok(self.alter_trans_table())
+    }
+}
+
+impl<TransTable: TransitionTable> Trie<TransTable>
+where
+    TransTable::KeyType: Ord,
+{
+    /// Packs the transitions of all nodes into a single edge array,
+    /// sorted by keys within every node, see [`FlatTransTable`].
+    pub fn freeze(&self) -> Trie<FlatTransTable<TransTable::KeyType, TransTable::NodeID>> {
+        let tables = FlatTransTable::build_all(self.node_pool.iter().map(|node| &node.trans));
+        Trie {
+            node_pool: self
+                .node_pool
+                .iter()
+                .zip(tables)
+                .map(|(node, trans)| TrieNode {
+                    trans,
+                    parent: node.parent,
+                    accept: node.accept,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<TransTable: TransitionTable> Trie<TransTable>
+where
+    TransTable::KeyType: DoubleArrayKey,
+{
+    /// Packs the transitions of all nodes into a single double array,
+    /// see [`DoubleArrayTable`].
+    pub fn to_double_array(
+        &self,
+    ) -> Trie<DoubleArrayTable<TransTable::KeyType, TransTable::NodeID>> {
+        let tables = DoubleArrayTable::build_all(self.node_pool.iter().map(|node| &node.trans));
+        Trie {
+            node_pool: self
+                .node_pool
+                .iter()
+                .zip(tables)
+                .map(|(node, trans)| TrieNode {
+                    trans,
+                    parent: node.parent,
+                    accept: node.accept,
+                })
+                .collect(),
+        }
+    }
 }
 
 impl<TransTable: ConstructiveTransitionTable> Trie<TransTable> {
-    fn alloc_node(&mut self, parent: TrieNodeID) -> TrieNodeID {
+    /// Fails before the pool grows if the node id overflows [`TransitionTable::NodeID`].
+    fn alloc_node(&mut self, parent: TrieNodeID) -> Result<TrieNodeID, NodeIdOverflow> {
         let node_id = self.node_pool.len();
+        NodeIdOverflow::check::<TransTable::NodeID>(node_id + 1)?;
         self.node_pool.push(TrieNode::new(parent));
-        node_id
+        Ok(node_id)
     }
 
+    /// # Panics
+    ///
+    /// Panics if the node ids overflow [`TransitionTable::NodeID`],
+    /// see [`Trie::try_insert`].
     pub fn insert<Iter: IntoIterator<Item = TransTable::KeyType>>(
         &mut self,
         iter: Iter,
     ) -> TrieNodeID {
+        self.try_insert(iter).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Like [`Trie::insert`],
+    /// but fails if the node ids overflow [`TransitionTable::NodeID`].
+    ///
+    /// On failure, the nodes of the keys before the overflowing one are kept,
+    /// while none of them is marked as accepting.
+    pub fn try_insert<Iter: IntoIterator<Item = TransTable::KeyType>>(
+        &mut self,
+        iter: Iter,
+    ) -> Result<TrieNodeID, NodeIdOverflow> {
         let mut current = TRIE_ROOT_NODE_ID;
-        iter.into_iter().for_each(|t| {
-            current = match self.node_pool[current].trans.get(&t) {
-                Some(v) => *v,
+        for t in iter {
+            current = match self.node_pool[current].trans.get_node_id(&t) {
+                Some(v) => v,
                 None => {
-                    let new_node_id = self.alloc_node(current);
-                    self.node_pool[current].trans.insert(t, new_node_id);
+                    let new_node_id = self.alloc_node(current)?;
+                    self.node_pool[current]
+                        .trans
+                        .insert(t, TransTable::NodeID::from_index(new_node_id));
                     new_node_id
                 }
             };
-        });
+        }
         self.node_pool[current].accept = true;
-        current
+        Ok(current)
     }
 }
 
@@ -185,7 +292,7 @@ impl<TransTable: TransitionTable, TrieRef: Deref<Target = Trie<TransTable>>>
 
     pub fn goto_parent(&mut self) {
         if let Some(node) = self.get_node() {
-            self.node_id = node.parent;
+            self.node_id = node.get_parent();
         } else {
             self.node_id = TRIE_NIL_NODE_ID;
         }
@@ -195,8 +302,7 @@ impl<TransTable: TransitionTable, TrieRef: Deref<Target = Trie<TransTable>>>
         if let Some(node) = self.get_node() {
             self.node_id = node
                 .trans
-                .get(t.borrow())
-                .copied()
+                .get_node_id(t.borrow())
                 .unwrap_or(TRIE_NIL_NODE_ID)
         } else {
             self.node_id = TRIE_NIL_NODE_ID;
@@ -253,6 +359,68 @@ impl<'s, TransTable: TransitionTable> Iterator for NextTrieStateIter<'s, TransTa
     fn next(&mut self) -> Option<Self::Item> {
         self.iter
             .next()
-            .map(|(t, next_node_id)| (t.clone(), self.trie.get_state(*next_node_id)))
+            .map(|(t, next_node_id)| (t.clone(), self.trie.get_state(next_node_id.index())))
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serialize {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer};
+
+    use super::{TRIE_NIL_NODE_ID, TRIE_ROOT_NODE_ID, Trie, TrieNode};
+    use crate::{NodeIndex, TransitionTable};
+
+    #[derive(Deserialize)]
+    #[serde(bound = "TransTable: Deserialize<'de>, TransTable::NodeID: Deserialize<'de>")]
+    struct RawTrie<TransTable: TransitionTable> {
+        node_pool: Vec<TrieNode<TransTable>>,
+    }
+
+    /// Checks that every parent is allocated before its children,
+    /// and every transition goes to a child of the node,
+    /// so the nodes form a tree.
+    fn validate<TransTable: TransitionTable>(
+        node_pool: &[TrieNode<TransTable>],
+    ) -> Result<(), String> {
+        if node_pool.len() < 2 {
+            return Err(format!(
+                "expected at least 2 nodes, got {}",
+                node_pool.len()
+            ));
+        }
+        if node_pool[TRIE_ROOT_NODE_ID].get_parent() != TRIE_NIL_NODE_ID {
+            return Err("invalid root node".to_owned());
+        }
+        for (node_id, node) in node_pool.iter().enumerate() {
+            if node_id > TRIE_ROOT_NODE_ID
+                && !(TRIE_ROOT_NODE_ID..node_id).contains(&node.get_parent())
+            {
+                return Err(format!("invalid parent of node {node_id}"));
+            }
+            for next_node_id in node.trans.transitions() {
+                let next_node_id = next_node_id.index();
+                if !node_pool
+                    .get(next_node_id)
+                    .is_some_and(|x| next_node_id > TRIE_ROOT_NODE_ID && x.get_parent() == node_id)
+                {
+                    return Err(format!("invalid transition of node {node_id}"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    impl<'de, TransTable: TransitionTable + Deserialize<'de>> Deserialize<'de> for Trie<TransTable>
+    where
+        TransTable::NodeID: Deserialize<'de>,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let raw = RawTrie::<TransTable>::deserialize(deserializer)?;
+            validate(&raw.node_pool).map_err(D::Error::custom)?;
+            Ok(Self {
+                node_pool: raw.node_pool,
+            })
+        }
     }
 }
@@ -6,8 +6,8 @@ use std::ops::Deref;
 
 use crate::rope::{Rope, RopeBase, RopeData, RopeUntaggedInner, TreapBasedRopeBase};
 use crate::{
-    GeneralSam, GeneralSamState, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID, TransitionTable, TravelEvent,
-    TrieNodeAlike,
+    GeneralSam, GeneralSamState, NodeIndex, SAM_NIL_NODE_ID, SAM_ROOT_NODE_ID, TransitionTable,
+    TravelEvent, TrieNodeAlike,
 };
 
 #[derive(Clone, Default, Debug)]
@@ -107,12 +107,10 @@ impl<Inner: RopeData + Default> SuffixwiseData<Inner> {
                 );
             }
 
-            node.get_trans()
-                .transitions()
-                .copied()
-                .for_each(|target_id| {
-                    res[target_id].data = res[target_id].data.merge(&res[node_id].data)
-                });
+            node.get_trans().transitions().for_each(|target_id| {
+                let target_id = target_id.index();
+                res[target_id].data = res[target_id].data.merge(&res[node_id].data)
+            });
         }
         res
     }